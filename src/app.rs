@@ -1,23 +1,123 @@
 use crate::{
     button,
-    ButtonVariant, SECONDARY, GREY_WHITE, PRIMARY_COLOR, icon_button
+    ButtonVariant, GREEN, GREY_WHITE, PRIMARY_COLOR, YELLOW, icon_button
 };
 
-use std::{fmt::{self, Formatter}, ops::RangeInclusive};
-use egui::{Ui, Response, WidgetText, Align2, Vec2, Color32, style::WidgetVisuals, Rounding, Stroke};
-use log::debug;
+use std::{fmt::{self, Formatter}, ops::RangeInclusive, time::{Duration, Instant}};
+use anyhow::{bail, ensure};
+use egui::{Ui, Response, Align2, Vec2};
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
-use egui_plot::{Line, Plot, PlotPoints};
+use egui_plot::{Legend, LineStyle, MarkerShape, Plot, PlotBounds};
 use egui_toast::{self, Toasts, Toast, ToastOptions, ToastKind};
 
+mod acceptance;
+mod analysis;
+mod csv_export;
+mod filter;
+mod history;
+mod journal;
+mod machine_profile;
+mod overlay;
+mod plot_image;
+mod report;
+mod sample;
 mod serial_driver;
+mod series;
+mod speed;
+mod test_file;
+mod test_metadata;
+mod test_program;
+mod units;
 mod icons;
-use self::serial_driver::SerialDriver;
+use self::acceptance::{AcceptanceCriteria, ResultLimit, ToleranceBand, Verdict};
+use self::csv_export::{Column, CsvSettings, DecimalSeparator, Delimiter, SEPARATOR_CLASH};
+use self::analysis::{Analysis, AnalysisSettings, FitWindow, Specimen};
+use self::filter::{FilterKind, FilterSettings};
+use self::history::{History, HistoryFilter};
+use self::journal::Journal;
+use self::machine_profile::{Compliance, MachineProfile};
+use self::overlay::{Alignment, Overlay};
+use self::plot_image::{ImageFormat, PlotImage, PlotLine, PlotMarker};
+use self::report::{result_key, Block, Chart, Language, Report, ReportTemplate, Section};
+use self::sample::Sample;
+use self::serial_driver::{SerialDriver, POSITION_SCALE};
+use self::series::{ResultValue, Series, SeriesTest};
+use self::speed::{RateSwitch, SpeedUnit, MAX_CROSSHEAD_SPEED, MIN_CROSSHEAD_SPEED};
+use self::test_file::TestFile;
+use self::test_metadata::{TestInfo, TestMetadata};
+use self::units::{ForceUnit, LengthUnit, Quantity, Scale, StressUnit, Units};
+use self::test_program::{CyclicSettings, HoldSettings, LimitKind, Measurement, ProgramRunner, Stage, StageKind, TestMode, TestProcedure, TestProgram, MAX_CYCLES};
 
 const INDUSTRIO_LOGO: egui::ImageSource<'_> = egui::include_image!("../assets/logo.png");
 const SPECIMEN_DIAGRAM: egui::ImageSource<'_> = egui::include_image!("../assets/specimen.png");
 
+/// Furthest the crosshead travels looking for the preload force, in mm.
+const PRELOAD_MAX_TRAVEL: f64 = 50.0;
+
+/// Force in N a test run from the host has to reach before a drop in force counts as a break.
+const MIN_BREAK_FORCE: f64 = 5.0;
+
+/// Most samples kept in memory, long tests are thinned out beyond this.
+const MAX_SAMPLES: usize = 100_000;
+
+/// Rows of the cycle results table shown at once, long tests run to
+/// millions of cycles.
+const CYCLES_PER_PAGE: usize = 50;
+
+/// How a test stopped by the operator is marked.
+const CANCELLED: &str = "Cancelled by the operator";
+
+/// While a test runs its analysis is worked out at most this often, in s.
+/// The curve follows every sample.
+const LIVE_ANALYSIS_INTERVAL: f64 = 0.5;
+
+/// Colour of the current test's curve.
+const CURVE_COLOR: egui::Color32 = egui::Color32::from_rgb(188, 77, 77);
+
+/// What the analysis of the current test is worked out from. The compliance
+/// and parameters of a test only change along with its samples.
+#[derive(Clone, Copy, PartialEq)]
+struct AnalysisInputs {
+    samples_version: u64,
+    filter_settings: FilterSettings,
+    analysis_settings: AnalysisSettings,
+}
+
+/// Peak forces of the running test, kept up with every sample so stopping
+/// on a break or a failed cycle doesn't wait for the analysis.
+#[derive(Default)]
+struct LivePeaks {
+    /// Highest force so far in N.
+    max_force: f64,
+    /// Force of the last sample in N.
+    force: f64,
+    /// Highest force of every cycle so far, for cyclic tests.
+    cycles: Vec<f64>,
+}
+
+impl LivePeaks {
+    fn add(&mut self, sample: &Sample, procedure: TestProcedure) {
+        self.max_force = self.max_force.max(sample.force);
+        self.force = sample.force;
+
+        if procedure == TestProcedure::Cyclic {
+            // Every cycle is a load and an unload stage.
+            let cycle = sample.stage / 2;
+            if self.cycles.len() <= cycle {
+                self.cycles.resize(cycle + 1, f64::MIN);
+            }
+            self.cycles[cycle] = self.cycles[cycle].max(sample.force);
+        }
+    }
+
+    /// The force collapsed after a peak high enough to count, like
+    /// `TensileResults::broken`.
+    fn broken(&self) -> bool {
+        self.max_force >= MIN_BREAK_FORCE && self.force < self.max_force * analysis::BREAK_FORCE_FRACTION
+    }
+}
+
 #[derive(PartialEq, Debug, Clone, Copy)]
 enum ConnectionState {
     Connecting,
@@ -26,7 +126,7 @@ enum ConnectionState {
 }
 
 impl fmt::Display for ConnectionState {
-    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(f, "{:?}", self)
     }
 }
@@ -56,36 +156,279 @@ impl fmt::Display for BaudRate {
     }
 }
 
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, EnumIter)]
+enum PlotMode {
+    ForceExtension,
+    StressStrain,
+    TrueStressStrain,
+    ForceTime,
+    ExtensionTime,
+    /// Any two channels picked by the user.
+    Custom,
+}
+
+impl fmt::Display for PlotMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PlotMode::ForceExtension => write!(f, "Force–extension"),
+            PlotMode::StressStrain => write!(f, "Stress–strain"),
+            PlotMode::TrueStressStrain => write!(f, "True stress–strain"),
+            PlotMode::ForceTime => write!(f, "Force–time"),
+            PlotMode::ExtensionTime => write!(f, "Extension–time"),
+            PlotMode::Custom => write!(f, "Custom"),
+        }
+    }
+}
+
+impl PlotMode {
+    fn needs_specimen(&self) -> bool {
+        matches!(self, PlotMode::StressStrain | PlotMode::TrueStressStrain)
+    }
+
+    /// The X and Y channels plotted, `None` for true stress–strain which is
+    /// not made of recorded channels.
+    fn channels(&self, custom: (PlotChannel, PlotChannel)) -> Option<(PlotChannel, PlotChannel)> {
+        match self {
+            PlotMode::ForceExtension => Some((PlotChannel::Extension, PlotChannel::Force)),
+            PlotMode::StressStrain => Some((PlotChannel::Strain, PlotChannel::Stress)),
+            PlotMode::TrueStressStrain => None,
+            PlotMode::ForceTime => Some((PlotChannel::Time, PlotChannel::Force)),
+            PlotMode::ExtensionTime => Some((PlotChannel::Time, PlotChannel::Extension)),
+            PlotMode::Custom => Some(custom),
+        }
+    }
+
+    fn is_available(&self, test_mode: TestMode, has_specimen: bool) -> bool {
+        // True stress and strain assume a specimen that necks down in tension.
+        let fits_mode = test_mode == TestMode::Tension || *self != PlotMode::TrueStressStrain;
+        fits_mode && (has_specimen || !self.needs_specimen())
+    }
+}
+
+/// A recorded or derived quantity that can go on a plot axis.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, EnumIter)]
+enum PlotChannel {
+    Time,
+    Position,
+    Extension,
+    Strain,
+    Force,
+    Stress,
+}
+
+impl fmt::Display for PlotChannel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PlotChannel::Time => write!(f, "Time"),
+            PlotChannel::Position => write!(f, "Position"),
+            PlotChannel::Extension => write!(f, "Extension"),
+            PlotChannel::Strain => write!(f, "Strain"),
+            PlotChannel::Force => write!(f, "Force"),
+            PlotChannel::Stress => write!(f, "Stress"),
+        }
+    }
+}
+
+impl PlotChannel {
+    fn needs_specimen(&self) -> bool {
+        matches!(self, PlotChannel::Strain | PlotChannel::Stress)
+    }
+
+    fn unit(&self, units: &Units) -> Scale {
+        match self {
+            PlotChannel::Time => Scale::seconds(),
+            PlotChannel::Position | PlotChannel::Extension => units.length(),
+            PlotChannel::Strain => units.strain(),
+            PlotChannel::Force => units.force(),
+            PlotChannel::Stress => units.stress(),
+        }
+    }
+
+    /// Axis label with the unit, e.g. "Force (kN)".
+    fn label(&self, units: &Units) -> String {
+        format!("{} ({})", self, self.unit(units).symbol)
+    }
+
+    /// The channel of `sample` in the shown unit, `point` being its filtered
+    /// and corrected `[extension, force]`.
+    fn value(&self, sample: &Sample, [extension, force]: [f64; 2], specimen: &Specimen, units: &Units) -> f64 {
+        let value = match self {
+            PlotChannel::Time => sample.time,
+            PlotChannel::Position => sample.position,
+            PlotChannel::Extension => extension,
+            PlotChannel::Strain => extension / specimen.gauge_length * 100.0,
+            PlotChannel::Force => force,
+            PlotChannel::Stress => force / specimen.area,
+        };
+        self.unit(units).show(value)
+    }
+
+    /// The channel at an engineering `[strain, stress]` point, `None` when
+    /// the channel does not follow from it.
+    fn stress_strain_value(&self, point: [f64; 2], specimen: &Specimen, units: &Units) -> Option<f64> {
+        let [extension, force] = specimen.force_extension(point);
+        let value = match self {
+            PlotChannel::Time | PlotChannel::Position => return None,
+            PlotChannel::Extension => extension,
+            PlotChannel::Strain => point[0] * 100.0,
+            PlotChannel::Force => force,
+            PlotChannel::Stress => point[1],
+        };
+        Some(self.unit(units).show(value))
+    }
+}
+
 #[derive(serde::Deserialize, serde::Serialize, Clone, Copy)]
 pub struct UserPreferences {
     save_connection_settings: bool,
     auto_connect_on_startup: bool,
+    #[serde(default)]
+    units: Units,
 }
 
 impl Default for UserPreferences {
     fn default() -> Self {
         UserPreferences {
             save_connection_settings: true,
-            auto_connect_on_startup: false
+            auto_connect_on_startup: false,
+            units: Default::default(),
         }
     }
 }
 
-#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy)]
+#[serde(default)]
+pub struct PreloadSettings {
+    enabled: bool,
+    force: f64,
+    speed: f64,
+    hold_time: f64,
+}
+
+impl Default for PreloadSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            force: 5.0,
+            speed: 0.1,
+            hold_time: 2.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum PreloadPhase {
+    /// Moving slowly towards the preload force, from a target position.
+    Loading { target: f32 },
+    Holding { until: Instant },
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+#[serde(default)]
 pub struct TestParamters {
+    /// Test speed, in `speed_unit`.
     speed: f64,
+    speed_unit: SpeedUnit,
+    /// Modulus in MPa expected from the material, converts stress rates into crosshead speed.
+    expected_modulus: f64,
+    rate_switch: RateSwitch,
+    mode: TestMode,
     area: f64,
-    max_distance: f64
+    gauge_length: f64,
+    /// Distance between the supports of a bend test in mm.
+    span: f64,
+    /// Width of a bend specimen in mm.
+    width: f64,
+    /// Thickness of a bend specimen in mm.
+    thickness: f64,
+    max_distance: f64,
+    preload: PreloadSettings,
+    procedure: TestProcedure,
+    program: TestProgram,
+    cyclic: CyclicSettings,
+    relaxation: HoldSettings,
+    creep: HoldSettings,
 }
 
 impl Default for TestParamters {
     fn default() -> Self {
         Self {
             speed: 1.0,
+            speed_unit: Default::default(),
+            expected_modulus: 210_000.0,
+            rate_switch: Default::default(),
+            mode: Default::default(),
             area: Default::default(),
-            max_distance: Default::default()
+            gauge_length: Default::default(),
+            span: Default::default(),
+            width: Default::default(),
+            thickness: Default::default(),
+            max_distance: Default::default(),
+            preload: Default::default(),
+            procedure: Default::default(),
+            program: Default::default(),
+            cyclic: Default::default(),
+            relaxation: Default::default(),
+            creep: HoldSettings { target: 100.0, ..Default::default() },
+        }
+    }
+}
+
+impl TestParamters {
+    fn specimen(&self) -> Specimen {
+        match self.mode {
+            TestMode::Tension | TestMode::Compression => Specimen { area: self.area, gauge_length: self.gauge_length },
+            TestMode::Flexure => Specimen::three_point_bend(self.span, self.width, self.thickness),
+        }
+    }
+
+    /// These parameters with the specimen geometry of `specimen`.
+    fn with_geometry_of(&self, specimen: &TestParamters) -> Self {
+        Self {
+            area: specimen.area,
+            gauge_length: specimen.gauge_length,
+            span: specimen.span,
+            width: specimen.width,
+            thickness: specimen.thickness,
+            ..self.clone()
         }
     }
+
+    /// Converts a speed into the crosshead speed in mm/s.
+    fn crosshead_speed(&self, speed: f64, unit: SpeedUnit) -> anyhow::Result<f64> {
+        let Some(crosshead_speed) = unit.crosshead_speed(speed, &self.specimen(), self.expected_modulus) else {
+            bail!(match unit {
+                SpeedUnit::Crosshead => "Invalid speed",
+                SpeedUnit::StrainRate => "Strain rates need the specimen geometry",
+                SpeedUnit::StressRate => "Stress rates need the specimen geometry and the expected modulus",
+            });
+        };
+
+        ensure!(
+            (MIN_CROSSHEAD_SPEED..=MAX_CROSSHEAD_SPEED).contains(&crosshead_speed),
+            "{speed}{unit} is a crosshead speed of {crosshead_speed:.3}mm/s, outside the {MIN_CROSSHEAD_SPEED}–{MAX_CROSSHEAD_SPEED}mm/s the machine runs at"
+        );
+        Ok(crosshead_speed)
+    }
+
+    /// The test speed in mm/s.
+    fn pull_speed(&self) -> anyhow::Result<f64> {
+        self.crosshead_speed(self.speed, self.speed_unit)
+    }
+
+    /// The program for a single pull run from the host, `None` when the firmware runs it.
+    fn pull_program(&self) -> anyhow::Result<Option<TestProgram>> {
+        let speed = self.pull_speed()?;
+
+        if self.rate_switch.enabled {
+            let switch = &self.rate_switch;
+            let then_speed = self.crosshead_speed(switch.speed, switch.unit)?;
+            return Ok(Some(TestProgram::switched_move(speed, switch.limit, switch.at, then_speed, self.max_distance)));
+        }
+
+        // The firmware can only pull at one speed, the other modes run the move from here.
+        Ok((self.mode != TestMode::Tension).then(|| TestProgram::single_move(speed, self.max_distance)))
+    }
 }
 
 #[derive(serde::Deserialize, serde::Serialize)]
@@ -98,12 +441,45 @@ pub struct TensileTestingApp {
     serial_port: String,
     baud_rate: String,
     
+    machine_profile: MachineProfile,
     test_parameters: TestParamters,
+    /// Filled into the metadata of the next test.
+    test_info: TestInfo,
+    analysis_settings: AnalysisSettings,
+    filter_settings: FilterSettings,
+    csv_settings: CsvSettings,
+    report_templates: Vec<ReportTemplate>,
+    /// Index into `report_templates` of the template reports are written with.
+    report_template: usize,
+    /// The series finished tests are added to, if one is running.
+    series: Option<Series>,
+    /// What finished tests are checked against.
+    acceptance: AcceptanceCriteria,
+    plot_mode: PlotMode,
+    /// X and Y channels of the custom plot mode.
+    plot_channels: (PlotChannel, PlotChannel),
+    /// Keep the whole curve in view while a test is running.
+    follow_test: bool,
+    overlay_alignment: Alignment,
+    /// Width and height in pixels plots are exported at.
+    plot_image_size: [u32; 2],
     jog_control_step_distance: f32,
 
     is_testing: bool,
     #[serde(skip)]
-    start_data_point: Option<(f64, f64)>,
+    start_data_point: Option<(Instant, f64, f64)>,
+    #[serde(skip)]
+    last_sample: Option<u32>,
+    /// Only every `sample_stride`th reading is recorded, doubled each time the data is thinned out.
+    #[serde(skip)]
+    sample_stride: u32,
+    #[serde(skip)]
+    preload_phase: Option<PreloadPhase>,
+    #[serde(skip)]
+    program_runner: Option<ProgramRunner>,
+    /// Force reading taken before preloading, used as zero force for the test.
+    #[serde(skip)]
+    force_tare: Option<f64>,
 
     #[serde(skip)]
     driver : SerialDriver,
@@ -111,8 +487,88 @@ pub struct TensileTestingApp {
     #[serde(skip)]
     toast: Toasts,
 
+    /// Raw samples of the current test, never modified by filtering.
+    #[serde(skip)]
+    data_points : Vec<Sample>,
+    /// Bumped whenever `data_points` change, including for another test.
+    #[serde(skip)]
+    samples_version: u64,
+    /// What `analysis` was last worked out from.
+    #[serde(skip)]
+    analysed: Option<AnalysisInputs>,
+    /// When `analysis` was last worked out.
+    #[serde(skip)]
+    analysed_at: Option<Instant>,
+    /// The `samples_version` `curve` was last worked out from.
+    #[serde(skip)]
+    curve_version: Option<u64>,
+    #[serde(skip)]
+    live_peaks: LivePeaks,
+
+    #[serde(skip)]
+    test_metadata: TestMetadata,
+
+    /// Filtered and compliance corrected `[extension, force]` curve used for
+    /// display and analysis.
+    #[serde(skip)]
+    curve: Vec<[f64; 2]>,
+
+    #[serde(skip)]
+    analysis: Analysis,
+
+    /// Outcome of the acceptance checks of the last test.
+    #[serde(skip)]
+    verdict: Option<Verdict>,
+
+    /// Page of the cycle results table in view.
+    #[serde(skip)]
+    cycle_page: usize,
+
+    /// Other tests drawn over the current one.
+    #[serde(skip)]
+    overlays: Vec<Overlay>,
+
+    /// The part of the plot in view, exported plots show the same.
+    #[serde(skip)]
+    plot_bounds: Option<PlotBounds>,
+
+    /// Whether the window listing saved tests is open.
+    #[serde(skip)]
+    open_test_window: bool,
+    /// Test file path typed into the open window.
+    #[serde(skip)]
+    open_test_path: String,
+    /// Test files in the export folder, listed when the open window opens.
+    #[serde(skip)]
+    saved_tests: Vec<std::path::PathBuf>,
+
+    /// Every completed test, stored automatically.
+    #[serde(skip)]
+    history: History,
+    #[serde(skip)]
+    history_filter: HistoryFilter,
+    #[serde(skip)]
+    history_window: bool,
+    /// The history entry waiting for the operator to confirm its deletion.
+    #[serde(skip)]
+    history_delete: Option<u64>,
+
+    #[serde(skip)]
+    report_template_window: bool,
+    #[serde(skip)]
+    series_window: bool,
+
+    /// Journal of the running test on disk.
+    #[serde(skip)]
+    journal: Option<Journal>,
+    /// Test found in a journal left behind by a crash, until the user
+    /// recovers or discards it.
     #[serde(skip)]
-    data_points : Vec<[f64;2]>,
+    interrupted_test: Option<TestFile>,
+    /// H was pressed during a test, homing waits for the operator to
+    /// confirm cancelling it.
+    #[serde(skip)]
+    home_confirm: bool,
 }
 
 impl Default for TensileTestingApp {
@@ -122,13 +578,56 @@ impl Default for TensileTestingApp {
             user_preferences: UserPreferences::default(),
             serial_port: Default::default(),
             baud_rate: Default::default(),
+            machine_profile: Default::default(),
             test_parameters: Default::default(),
+            test_info: Default::default(),
+            analysis_settings: Default::default(),
+            filter_settings: Default::default(),
+            csv_settings: Default::default(),
+            report_templates: vec![ReportTemplate::default()],
+            report_template: 0,
+            series: None,
+            acceptance: Default::default(),
+            plot_mode: PlotMode::ForceExtension,
+            plot_channels: (PlotChannel::Time, PlotChannel::Force),
+            follow_test: true,
+            overlay_alignment: Default::default(),
+            plot_image_size: [1600, 800],
             jog_control_step_distance: 1.0,
             is_testing: false,
             start_data_point: Default::default(),
+            last_sample: None,
+            sample_stride: 1,
+            preload_phase: None,
+            program_runner: None,
+            force_tare: None,
             driver : SerialDriver::new(),
             toast: Toasts::new().anchor(Align2::LEFT_TOP, (10.0, 10.0)).direction(egui::Direction::TopDown),
             data_points : Vec::new(),
+            samples_version: 0,
+            analysed: None,
+            analysed_at: None,
+            curve_version: None,
+            live_peaks: Default::default(),
+            test_metadata: Default::default(),
+            curve: Vec::new(),
+            analysis: Default::default(),
+            verdict: None,
+            cycle_page: 0,
+            overlays: Vec::new(),
+            plot_bounds: None,
+            open_test_window: false,
+            open_test_path: String::new(),
+            saved_tests: Vec::new(),
+            history: Default::default(),
+            history_filter: Default::default(),
+            history_window: false,
+            history_delete: None,
+            report_template_window: false,
+            series_window: false,
+            journal: None,
+            interrupted_test: None,
+            home_confirm: false,
         }
     }
 }
@@ -146,31 +645,274 @@ impl TensileTestingApp {
 
         // Load previous app state (if any).
         // Note that you must enable the `persistence` feature for this to work.
-        if let Some(storage) = cc.storage {
-            return eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default();
+        let mut app: Self = cc.storage
+            .and_then(|storage| eframe::get_value(storage, eframe::APP_KEY))
+            .unwrap_or_default();
+
+        match History::default_folder().map(History::open) {
+            Some(Ok(history)) => app.history = history,
+            Some(Err(err)) => {
+                app.toast.add(Toast {
+                    text: format!("Test history unavailable: {err}").into(),
+                    kind: ToastKind::Info,
+                    options: ToastOptions::default().duration_in_seconds(3.0)
+                });
+            },
+            None => {},
+        }
+
+        if let Some(path) = Journal::default_path().filter(|path| path.exists()) {
+            match journal::recover(&path) {
+                Ok(test) => app.interrupted_test = Some(test),
+                Err(err) => {
+                    app.toast.add(Toast {
+                        text: format!("The interrupted test can't be recovered: {err}").into(),
+                        kind: ToastKind::Info,
+                        options: ToastOptions::default().duration_in_seconds(3.0)
+                    });
+                },
+            }
         }
 
-        Default::default()
+        app
     }
 
     fn plot_ui(&mut self, ui: &mut egui::Ui) {
-        // let sin: PlotPoints = (0..1000).map(|i| {
-        //     let x = i as f64 * 0.01;
-        //     [x, x.sin() * 3.0]
-        // }).collect();
-        // //let sin = PlotPoints::default();
-        let sin : PlotPoints = self.data_points.clone().into();
-
-        let line = Line::new(sin);
-        Plot::new("my_plot").view_aspect(2.0)
-        .label_formatter(|name, value| {
-            if !name.is_empty() {
-                format!("{}: {:.*}%", name, 1, value.y)
+        let has_specimen = self.test_metadata.parameters.specimen().is_valid();
+        let test_mode = self.test_metadata.parameters.mode;
+        if !self.plot_mode.is_available(test_mode, has_specimen) {
+            self.plot_mode = PlotMode::ForceExtension;
+        }
+        if !has_specimen && self.plot_channels.0.needs_specimen() {
+            self.plot_channels.0 = PlotChannel::Extension;
+        }
+        if !has_specimen && self.plot_channels.1.needs_specimen() {
+            self.plot_channels.1 = PlotChannel::Force;
+        }
+
+        let mut axes_changed = false;
+        ui.horizontal(|ui| {
+            for mode in PlotMode::iter() {
+                ui.add_enabled_ui(mode.is_available(test_mode, has_specimen), |ui| {
+                    axes_changed |= ui.selectable_value(&mut self.plot_mode, mode, mode.to_string()).changed();
+                });
+            }
+
+            if self.plot_mode == PlotMode::Custom {
+                ui.separator();
+                axes_changed |= plot_channel_ui(ui, "plot_x_combobox", "X:", &mut self.plot_channels.0, has_specimen);
+                axes_changed |= plot_channel_ui(ui, "plot_y_combobox", "Y:", &mut self.plot_channels.1, has_specimen);
+            }
+
+            ui.separator();
+            ui.checkbox(&mut self.follow_test, "Follow test");
+        });
+
+        let lines = self.plot_lines();
+        let markers = self.plot_markers();
+        let (x_label, y_label) = self.axis_labels();
+        let follow = axes_changed || (self.follow_test && self.is_testing);
+
+        let response = Plot::new("test_curve_plot").view_aspect(2.0)
+        .legend(Legend::default())
+        .x_axis_label(x_label.clone())
+        .y_axis_label(y_label.clone())
+        .label_formatter(move |name, value| {
+            let readout = format!("{x_label}: {:.3}\n{y_label}: {:.3}", value.x, value.y);
+            if name.is_empty() {
+                readout
             } else {
-                "".to_owned()
+                format!("{name}\n{readout}")
+            }
+        })
+        .show(ui, |plot_ui| {
+            if follow {
+                plot_ui.set_auto_bounds(true.into());
+            }
+
+            for line in &lines {
+                plot_ui.line(line.line());
+            }
+
+            for marker in &markers {
+                plot_ui.points(marker.points());
             }
+        });
+        self.plot_bounds = Some(*response.transform.bounds());
+    }
+
+    /// Every line of the plot, in the order they are drawn.
+    fn plot_lines(&self) -> Vec<PlotLine> {
+        let mut lines = Vec::new();
+        if self.filter_settings.kind != FilterKind::None {
+            lines.push(PlotLine::new("Raw", GREY_WHITE.gamma_multiply(0.3), self.plot_curve(&sample::force_extension(&self.data_points))));
+        }
+        lines.extend(self.overlay_lines());
+        lines.extend(self.tolerance_band_lines());
+        lines.push(PlotLine::new("Current test", CURVE_COLOR, self.plot_curve(&self.curve)));
+        lines.extend(self.modulus_line());
+        lines.extend(self.offset_yield_line());
+        lines
+    }
+
+    fn plot_markers(&self) -> Vec<PlotMarker> {
+        let mut markers = self.yield_markers();
+        markers.extend(self.stage_markers());
+        markers
+    }
+
+    /// X and Y axis labels with units for the current plot mode.
+    fn axis_labels(&self) -> (String, String) {
+        let units = self.user_preferences.units;
+
+        match self.plot_mode.channels(self.plot_channels) {
+            Some((x, y)) => (x.label(&units), y.label(&units)),
+            None => ("True strain (%)".to_owned(), format!("True stress ({})", units.stress().symbol)),
+        }
+    }
+
+    /// An `[extension, force]` curve in the coordinates and units of the current plot mode.
+    fn plot_curve(&self, curve: &[[f64; 2]]) -> Vec<[f64; 2]> {
+        self.test_curve(&self.data_points, curve, &self.test_metadata.parameters.specimen())
+    }
+
+    /// The `[extension, force]` curve of any test's `samples` in the
+    /// coordinates and units of the current plot mode.
+    fn test_curve(&self, samples: &[Sample], curve: &[[f64; 2]], specimen: &Specimen) -> Vec<[f64; 2]> {
+        let units = self.user_preferences.units;
+
+        match self.plot_mode.channels(self.plot_channels) {
+            Some((x, y)) => samples.iter().zip(curve)
+                .map(|(sample, point)| [x.value(sample, *point, specimen, &units), y.value(sample, *point, specimen, &units)])
+                .collect(),
+            None => analysis::true_curve(&specimen.stress_strain(curve)).into_iter()
+                .map(|[strain, stress]| [strain * 100.0, units.stress().show(stress)])
+                .collect(),
+        }
+    }
+
+    /// The visible overlays, shifted along the extension to line them up
+    /// with the current test, or with the first overlay without one.
+    fn overlay_lines(&self) -> Vec<PlotLine> {
+        let alignment = self.overlay_alignment;
+        let needs_specimen = match self.plot_mode.channels(self.plot_channels) {
+            Some((x, y)) => x.needs_specimen() || y.needs_specimen(),
+            None => true,
+        };
+        // Stress and strain of a test without specimen geometry can't be drawn.
+        let visible = || self.overlays.iter().filter(|overlay| overlay.visible && (overlay.specimen.is_valid() || !needs_specimen));
+        let reference = if self.curve.is_empty() {
+            visible().next().and_then(|overlay| alignment.point(&overlay.curve, &overlay.specimen, &overlay.analysis))
+        } else {
+            alignment.point(&self.curve, &self.test_metadata.parameters.specimen(), &self.analysis)
+        };
+
+        visible()
+            .map(|overlay| {
+                let point = alignment.point(&overlay.curve, &overlay.specimen, &overlay.analysis);
+                let shift = reference.zip(point).map_or(0.0, |(reference, point)| reference - point);
+                let curve: Vec<[f64; 2]> = overlay.curve.iter().map(|[extension, force]| [extension + shift, *force]).collect();
+                PlotLine::new(&overlay.name, overlay.color, self.test_curve(&overlay.samples, &curve, &overlay.specimen))
+            })
+            .collect()
+    }
+
+    /// Lower and upper edge of the tolerance band, while tests are checked against one.
+    fn tolerance_band_lines(&self) -> Vec<PlotLine> {
+        let Some(band) = self.acceptance.band.as_ref().filter(|_| self.acceptance.enabled) else {
+            return Vec::new();
+        };
+        if !self.test_metadata.parameters.specimen().is_valid() {
+            return Vec::new();
+        }
+
+        [1, 2].into_iter()
+            .filter_map(|edge| {
+                let points: Vec<[f64; 2]> = band.points.iter()
+                    .map(|point| self.plot_point([point[0], point[edge]]))
+                    .collect::<Option<_>>()?;
+                Some(PlotLine::new("Tolerance band", GREY_WHITE.gamma_multiply(0.6), points).style(LineStyle::dashed_dense()))
+            })
+            .collect()
+    }
+
+    /// Maps an engineering `[strain, stress]` point onto the current plot
+    /// mode, `None` when an axis is not a function of it, like time.
+    fn plot_point(&self, point: [f64; 2]) -> Option<[f64; 2]> {
+        let specimen = self.test_metadata.parameters.specimen();
+        let units = self.user_preferences.units;
+
+        match self.plot_mode.channels(self.plot_channels) {
+            Some((x, y)) => Some([x.stress_strain_value(point, &specimen, &units)?, y.stress_strain_value(point, &specimen, &units)?]),
+            None => {
+                let [strain, stress] = analysis::true_stress_strain(point);
+                Some([strain * 100.0, units.stress().show(stress)])
+            },
+        }
+    }
+
+    /// The modulus fit, drawn from zero stress to just past the end of the fit window.
+    fn modulus_line(&self) -> Option<PlotLine> {
+        let fit = self.analysis.modulus?;
+
+        let end_stress = fit.line.y(fit.strain_end) * 1.2;
+        let points: Vec<[f64; 2]> = [0.0, end_stress].iter()
+            .map(|stress| self.plot_point([fit.line.x(*stress), *stress]))
+            .collect::<Option<_>>()?;
+
+        Some(PlotLine::new("Modulus fit", YELLOW, points).style(LineStyle::dashed_loose()))
+    }
+
+    /// The offset construction line, from zero stress up to the offset yield point.
+    fn offset_yield_line(&self) -> Option<PlotLine> {
+        let fit = self.analysis.modulus?;
+        let yield_point = self.analysis.offset_yield?;
+
+        let start = [fit.line.x(0.0) + self.analysis.yield_offset, 0.0];
+        let points: Vec<[f64; 2]> = [start, yield_point].iter()
+            .map(|point| self.plot_point(*point))
+            .collect::<Option<_>>()?;
+
+        Some(PlotLine::new("Offset line", YELLOW, points).style(LineStyle::dotted_dense()))
+    }
+
+    /// Marks where each test program stage starts on the curve.
+    fn stage_markers(&self) -> Vec<PlotMarker> {
+        // Cycles are listed in the results instead, marking them all would bury the curve.
+        if self.test_metadata.procedure != TestProcedure::Program {
+            return Vec::new();
+        }
+
+        let program = self.test_metadata.program.as_ref();
+        let points = self.plot_curve(&self.curve);
+
+        sample::stage_boundaries(&self.data_points).into_iter()
+            .filter_map(|i| {
+                let point = points.get(i)?;
+                let stage = self.data_points[i].stage;
+                let name = match program.and_then(|program| program.stage(stage)) {
+                    Some(s) => format!("Stage {}: {}", stage + 1, s.describe(&self.user_preferences.units)),
+                    None => format!("Stage {}", stage + 1),
+                };
+                Some(PlotMarker::new(name, GREY_WHITE, MarkerShape::Diamond, *point))
+            })
+            .collect()
+    }
+
+    fn yield_markers(&self) -> Vec<PlotMarker> {
+        let offset_name = format!("Rp{}", self.analysis_settings.yield_offset);
+
+        [
+            (offset_name.as_str(), self.analysis.offset_yield),
+            ("ReH", self.analysis.upper_yield),
+            ("ReL", self.analysis.lower_yield),
+        ]
+        .into_iter()
+        .filter_map(|(name, point)| {
+            let point = self.plot_point(point?)?;
+            Some(PlotMarker::new(name, PRIMARY_COLOR, MarkerShape::Circle, point))
         })
-        .show(ui, |plot_ui| plot_ui.line(line));
+        .collect()
     }
     
     fn panel_ui(&mut self, ui: &mut egui::Ui) {
@@ -191,7 +933,7 @@ impl TensileTestingApp {
                     ui.label(state);
                     ui.end_row();
                     ui.label("Current position:");
-                    ui.label(format!("{}mm", self.driver.values().position));
+                    ui.label(self.user_preferences.units.length().format(self.driver.values().position_mm(), 2));
                     ui.end_row();
                 });
 
@@ -286,26 +1028,46 @@ impl TensileTestingApp {
             });
         }).fully_open();
 
+        self.test_info_panel(ui);
         self.specimen_settings_panel(ui);
+        self.test_procedure_panel(ui);
+        self.series_panel(ui);
+        self.analysis_panel(ui);
+        self.acceptance_panel(ui);
+        self.overlay_panel(ui);
+        self.machine_profile_panel(ui);
+        self.units_panel(ui);
+        self.export_panel(ui);
 
         egui::CollapsingHeader::new("Controls").open(Some(is_serial_connected(&self.connection_state))).show(ui, |ui| {
             egui::Frame::group(ui.style()).show(ui, |ui| {
                 ui.vertical_centered_justified(|ui| {
-                    ui.add_enabled_ui(is_serial_connected(&self.connection_state), |ui| {
+                    // Starting a test would overwrite the journal of the interrupted one.
+                    ui.add_enabled_ui(is_serial_connected(&self.connection_state) && self.interrupted_test.is_none(), |ui| {
                         ui.columns(3, |columns| {
                             if columns[0].add(icon_button(icons::PLAY_ARROW_ICON, "Start", ButtonVariant::Primary)).clicked() {
-                                let _ = self.driver.start_test(self.test_parameters.speed);
-                                self.is_testing = true;
-                                self.data_points.clear();
-                                self.start_data_point = None;
+                                // Specimens of a series only differ in their geometry.
+                                if let Some(series) = &self.series {
+                                    self.test_parameters = series.parameters.with_geometry_of(&self.test_parameters);
+                                }
+                                if self.test_parameters.preload.enabled {
+                                    self.start_preload();
+                                } else {
+                                    self.start_test();
+                                }
                             };
                             columns[1].add(icon_button(icons::PAUSE_ICON, "Pause", ButtonVariant::Secondary));
                             if columns[2].add(icon_button(icons::STOP_ICON, "Cancel", ButtonVariant::Secondary)).clicked() {
-                                self.driver.cancel_test();
-                                self.is_testing = false;
+                                self.cancel_test();
                             }
                         });
 
+                        match self.preload_phase {
+                            Some(PreloadPhase::Loading { .. }) => { ui.label("Preloading..."); },
+                            Some(PreloadPhase::Holding { .. }) => { ui.label("Holding preload..."); },
+                            None => {},
+                        }
+
                         ui.add_space(8.0);
                         ui.add_enabled_ui(!self.driver.is_acknowledge_pending(), |ui| {
                             ui.horizontal_wrapped(|ui| {
@@ -317,20 +1079,8 @@ impl TensileTestingApp {
                                 // debug!("ackknowledge pending in ui: {}", self.driver.is_acknowledge_pending());
                 
                                 if ui.add(egui::Button::image(icons::HOME_ICON)).on_hover_text("Home").clicked() {
-                                    self.is_testing = false;
                                     // actie wanneer de knop wordt ingedrukt
-                                    let result = self.driver.start_home();
-
-                                    match result {
-                                        Ok(_) => {},
-                                        Err(err) => {
-                                            self.toast.add(Toast {
-                                                text: err.to_string().into(),
-                                                kind: ToastKind::Info,
-                                                options: ToastOptions::default().duration_in_seconds(1.0)
-                                            });
-                                        },
-                                    };
+                                    self.home();
                                 }
                 
                                 if ui.add_enabled(self.driver.is_homed(), egui::Button::image(icons::FORWARD_ARROW_ICON)).on_hover_text("Jog forward").clicked() {
@@ -351,124 +1101,2407 @@ impl TensileTestingApp {
         });
     }
 
+    fn test_info_panel(&mut self, ui: &mut Ui) {
+        let info = &mut self.test_info;
+
+        egui::CollapsingHeader::new("Test information").show(ui, |ui| {
+            egui::Frame::group(ui.style()).show(ui, |ui| {
+                egui::Grid::new("test_info_grid")
+                .num_columns(2)
+                .spacing([0.0, 8.0])
+                .show(ui, |ui| {
+                    for (label, value) in [
+                        ("Operator:", &mut info.operator),
+                        ("Customer:", &mut info.customer),
+                        ("Material:", &mut info.material),
+                        ("Batch/lot:", &mut info.batch),
+                        ("Specimen ID:", &mut info.specimen_id),
+                    ] {
+                        ui.label(label);
+                        ui.text_edit_singleline(value);
+                        ui.end_row();
+                    }
+
+                    ui.label("Temperature:");
+                    optional_value_ui(ui, &mut info.temperature, "°C", 20.0);
+                    ui.end_row();
+
+                    ui.label("Humidity:");
+                    optional_value_ui(ui, &mut info.humidity, "%", 50.0);
+                    ui.end_row();
+                });
+
+                ui.add_space(8.0);
+                ui.label("Notes:");
+                ui.text_edit_multiline(&mut info.notes);
+
+                ui.separator();
+                let mut remove = None;
+                egui::Grid::new("custom_fields_grid")
+                .num_columns(3)
+                .spacing([4.0, 8.0])
+                .show(ui, |ui| {
+                    for (i, (name, value)) in info.custom_fields.iter_mut().enumerate() {
+                        ui.add(egui::TextEdit::singleline(name).hint_text("Field").desired_width(80.0));
+                        ui.add(egui::TextEdit::singleline(value).hint_text("Value").desired_width(120.0));
+                        if ui.small_button("✖").clicked() {
+                            remove = Some(i);
+                        }
+                        ui.end_row();
+                    }
+                });
+                if let Some(i) = remove {
+                    info.custom_fields.remove(i);
+                }
+                if render_full_width(ui, button("Add field", ButtonVariant::Secondary)).clicked() {
+                    info.custom_fields.push(Default::default());
+                }
+            })
+        });
+    }
+
     fn specimen_settings_panel(&mut self, ui: &mut Ui) {
+        let units = self.user_preferences.units;
+        let speed_range = RangeInclusive::new(units.speed().show(MIN_CROSSHEAD_SPEED), units.speed().show(MAX_CROSSHEAD_SPEED));
+
         egui::CollapsingHeader::new("Specimen settings").show(ui, |ui| {
             egui::Frame::group(ui.style()).show(ui, |ui| {
                 ui.vertical_centered_justified(|ui| {
-                    ui.allocate_ui(Vec2{x: 125.0, y: 80.0}, |ui| {
-                        ui.add(egui::Image::new(SPECIMEN_DIAGRAM));
+                    if self.test_parameters.mode != TestMode::Flexure {
+                        ui.allocate_ui(Vec2{x: 125.0, y: 80.0}, |ui| {
+                            ui.add(egui::Image::new(SPECIMEN_DIAGRAM));
 
-                        ui.add(egui::DragValue::new(&mut self.test_parameters.area))
-                    });
+                            ui.add(units.area().drag_value(&mut self.test_parameters.area))
+                        });
 
-                    ui.add_space(25.0);
+                        ui.add_space(25.0);
+                    }
 
                     egui::Grid::new("specimen_settings_grid")
                     .num_columns(2)
                     .spacing([0.0, 8.0])
                     .show(ui, |ui| {
-                        ui.label("Speed:");
-                        ui.add(egui::DragValue::new(&mut self.test_parameters.speed).suffix("mm/s").clamp_range(RangeInclusive::new(0.01, 10.0)));
+                        ui.label("Test mode:");
+                        egui::ComboBox::new("test_mode_combobox", "")
+                            .selected_text(self.test_parameters.mode.to_string())
+                            .show_ui(ui, |ui| {
+                                for mode in TestMode::iter() {
+                                    ui.selectable_value(&mut self.test_parameters.mode, mode, mode.to_string());
+                                }
+                            });
                         ui.end_row();
 
-                        ui.label("Max distance:");
-                        ui.add(egui::DragValue::new(&mut self.test_parameters.max_distance).suffix("mm"));
+                        ui.label("Speed:");
+                        speed_ui(ui, "speed_unit_combobox", &units, &mut self.test_parameters.speed, &mut self.test_parameters.speed_unit);
                         ui.end_row();
 
-                        ui.label("Specimen area:");
-                        ui.add(egui::DragValue::new(&mut self.test_parameters.area).suffix("mm²"));
+                        let rate_switch = &mut self.test_parameters.rate_switch;
+                        ui.label("Switch speed:");
+                        ui.checkbox(&mut rate_switch.enabled, "");
                         ui.end_row();
-                    });
-                })
-            })
-        });
-    }
-
-    fn update_data(&mut self) {
-        let opt_values = self.driver.update();
 
-        if let Some( values ) = opt_values {
-            if (self.is_testing) {
-                let x = values.position as f64/ 100.0;
-                let f = values.tensile as f64/ 10.0;
+                        if rate_switch.enabled {
+                            ui.label("Switch at:");
+                            ui.horizontal(|ui| {
+                                ui.add(units.limit(rate_switch.limit).drag_value(&mut rate_switch.at).speed(0.1).clamp_range(RangeInclusive::new(0.0, f64::MAX)));
+                                egui::ComboBox::new("rate_switch_limit_combobox", "")
+                                    .selected_text(rate_switch.limit.to_string())
+                                    .show_ui(ui, |ui| {
+                                        for limit in LimitKind::iter() {
+                                            ui.selectable_value(&mut rate_switch.limit, limit, limit.to_string());
+                                        }
+                                    });
+                            });
+                            ui.end_row();
 
-                // debug!("data update x:{}, f:{}", x, f);
+                            ui.label("Then:");
+                            speed_ui(ui, "rate_switch_unit_combobox", &units, &mut rate_switch.speed, &mut rate_switch.unit);
+                            ui.end_row();
+                        }
 
-                if let Some((start_pos, start_force)) = self.start_data_point {
-                    let pos = start_pos - x;
-                    let force = f - start_force;
-                    self.data_points.push([pos, force]);
-                } else {
-                    self.start_data_point = Some((x, f));
-                    self.data_points.push([0.0, 0.0]);
-                }
-    
-                if !self.driver.is_acknowledge_pending() {
-                    self.is_testing = false
-                }
-            }
-        }
+                        let rate_switch = &self.test_parameters.rate_switch;
+                        if self.test_parameters.speed_unit == SpeedUnit::StressRate || (rate_switch.enabled && rate_switch.unit == SpeedUnit::StressRate) {
+                            ui.label("Expected modulus:");
+                            ui.add(units.stress().drag_value(&mut self.test_parameters.expected_modulus).speed(100.0).clamp_range(RangeInclusive::new(0.0, f64::MAX)))
+                                .on_hover_text("Converts stress rates into crosshead speed in the elastic range");
+                            ui.end_row();
+                        }
 
-    }
+                        if self.test_parameters.speed_unit != SpeedUnit::Crosshead {
+                            ui.label("Crosshead speed:");
+                            match self.test_parameters.pull_speed() {
+                                Ok(speed) => ui.label(units.speed().format(speed, 3)),
+                                Err(err) => ui.label(err.to_string()),
+                            };
+                            ui.end_row();
+                        }
+
+                        ui.label("Max distance:");
+                        ui.add(units.length().drag_value(&mut self.test_parameters.max_distance))
+                            .on_hover_text("Furthest the crosshead moves, required for compression and bend tests");
+                        ui.end_row();
+
+                        if self.test_parameters.mode == TestMode::Flexure {
+                            ui.label("Support span:");
+                            ui.add(units.length().drag_value(&mut self.test_parameters.span).clamp_range(RangeInclusive::new(0.0, f64::MAX)));
+                            ui.end_row();
+
+                            ui.label("Width:");
+                            ui.add(units.length().drag_value(&mut self.test_parameters.width).speed(0.1).clamp_range(RangeInclusive::new(0.0, f64::MAX)));
+                            ui.end_row();
+
+                            ui.label("Thickness:");
+                            ui.add(units.length().drag_value(&mut self.test_parameters.thickness).speed(0.1).clamp_range(RangeInclusive::new(0.0, f64::MAX)));
+                            ui.end_row();
+                        } else {
+                            ui.label("Specimen area:");
+                            ui.add(units.area().drag_value(&mut self.test_parameters.area));
+                            ui.end_row();
+
+                            ui.label(if self.test_parameters.mode == TestMode::Compression { "Specimen height:" } else { "Gauge length:" });
+                            ui.add(units.length().drag_value(&mut self.test_parameters.gauge_length).clamp_range(RangeInclusive::new(0.0, f64::MAX)));
+                            ui.end_row();
+                        }
+                    });
+
+                    ui.add_space(8.0);
+                    ui.checkbox(&mut self.test_parameters.preload.enabled, "Preload before test");
+
+                    if self.test_parameters.preload.enabled {
+                        egui::Grid::new("preload_settings_grid")
+                        .num_columns(2)
+                        .spacing([0.0, 8.0])
+                        .show(ui, |ui| {
+                            ui.label("Preload:");
+                            ui.add(units.force().drag_value(&mut self.test_parameters.preload.force).clamp_range(RangeInclusive::new(0.0, f64::MAX)));
+                            ui.end_row();
+
+                            ui.label("Preload speed:");
+                            ui.add(units.speed().drag_value(&mut self.test_parameters.preload.speed).speed(0.01).clamp_range(speed_range));
+                            ui.end_row();
+
+                            ui.label("Hold time:");
+                            ui.add(egui::DragValue::new(&mut self.test_parameters.preload.hold_time).suffix("s").speed(0.1).clamp_range(RangeInclusive::new(0.0, 600.0)));
+                            ui.end_row();
+                        });
+                    }
+                })
+            })
+        });
+    }
+
+    fn test_procedure_panel(&mut self, ui: &mut Ui) {
+        egui::CollapsingHeader::new("Test procedure").show(ui, |ui| {
+            egui::Frame::group(ui.style()).show(ui, |ui| {
+                egui::ComboBox::new("test_procedure_combobox", "")
+                    .selected_text(self.test_parameters.procedure.to_string())
+                    .show_ui(ui, |ui| {
+                        for procedure in TestProcedure::iter() {
+                            ui.selectable_value(&mut self.test_parameters.procedure, procedure, procedure.to_string());
+                        }
+                    });
+                ui.add_space(8.0);
+
+                match self.test_parameters.procedure {
+                    TestProcedure::Pull => { ui.label("Loads at the test speed until the specimen breaks."); },
+                    TestProcedure::Program => self.test_program_ui(ui),
+                    TestProcedure::Cyclic => self.cyclic_settings_ui(ui),
+                    TestProcedure::Relaxation => {
+                        ui.label("Pulls to the target extension and holds it, recording the force.");
+                        hold_settings_ui(ui, "relaxation_settings_grid", &self.user_preferences.units, &mut self.test_parameters.relaxation, LimitKind::Extension);
+                    },
+                    TestProcedure::Creep => {
+                        ui.label("Pulls to the target force and holds it, recording the extension.");
+                        hold_settings_ui(ui, "creep_settings_grid", &self.user_preferences.units, &mut self.test_parameters.creep, LimitKind::Force);
+                    },
+                }
+            })
+        });
+    }
+
+    fn series_panel(&mut self, ui: &mut Ui) {
+        let mut start = false;
+        let mut end = false;
+
+        egui::CollapsingHeader::new("Series").show(ui, |ui| {
+            egui::Frame::group(ui.style()).show(ui, |ui| {
+                let Some(series) = &mut self.series else {
+                    ui.label("Groups the specimens of a material, tested with the same parameters.");
+                    ui.add_space(8.0);
+                    ui.add_enabled_ui(!self.is_testing, |ui| {
+                        start = render_full_width(ui, button("Start series", ButtonVariant::Secondary)).clicked();
+                    });
+                    return;
+                };
+
+                egui::Grid::new("series_grid")
+                .num_columns(2)
+                .spacing([0.0, 8.0])
+                .show(ui, |ui| {
+                    ui.label("Name:");
+                    ui.text_edit_singleline(&mut series.name);
+                    ui.end_row();
+
+                    ui.label("Tests:");
+                    ui.label(format!("{}, {} excluded", series.tests.len(), series.excluded_count()));
+                    ui.end_row();
+                });
+
+                ui.add_space(8.0);
+                ui.label("Tests run with the settings the series was started with, only the specimen geometry is taken from the specimen settings.");
+                ui.add_space(8.0);
+                ui.columns(2, |columns| {
+                    if columns[0].add(button("Results", ButtonVariant::Secondary)).clicked() {
+                        self.series_window = true;
+                    }
+                    end = columns[1].add_enabled(!self.is_testing, button("End series", ButtonVariant::Secondary)).clicked();
+                });
+            })
+        });
+
+        if start {
+            let name = match self.test_info.material.trim() {
+                "" => format!("Series {}", chrono::Local::now().format("%Y-%m-%d")),
+                material => material.to_owned(),
+            };
+            self.series = Some(Series {
+                name,
+                parameters: self.test_parameters.clone(),
+                machine: self.machine_profile.name.clone(),
+                compliance: self.machine_profile.compliance.filter(|_| self.machine_profile.correct_compliance),
+                tests: Vec::new(),
+            });
+        }
+        if end {
+            self.series = None;
+            self.series_window = false;
+        }
+    }
+
+    fn cyclic_settings_ui(&mut self, ui: &mut Ui) {
+        let cyclic = &mut self.test_parameters.cyclic;
+        let units = self.user_preferences.units;
+        let speed_range = RangeInclusive::new(units.speed().show(MIN_CROSSHEAD_SPEED), units.speed().show(MAX_CROSSHEAD_SPEED));
+
+        egui::Grid::new("cyclic_settings_grid")
+        .num_columns(2)
+        .spacing([0.0, 8.0])
+        .show(ui, |ui| {
+            ui.label("Cycle between:");
+            egui::ComboBox::new("cyclic_limit_combobox", "")
+                .selected_text(cyclic.limit.to_string())
+                .show_ui(ui, |ui| {
+                    for limit in [LimitKind::Extension, LimitKind::Force] {
+                        ui.selectable_value(&mut cyclic.limit, limit, limit.to_string());
+                    }
+                });
+            ui.end_row();
+
+            let limit = units.limit(cyclic.limit);
+            let (upper, lower) = (limit.show(cyclic.upper), limit.show(cyclic.lower));
+
+            ui.label("Upper limit:");
+            ui.add(limit.drag_value(&mut cyclic.upper).speed(0.1).clamp_range(RangeInclusive::new(lower, f64::MAX)));
+            ui.end_row();
+
+            ui.label("Lower limit:");
+            ui.add(limit.drag_value(&mut cyclic.lower).speed(0.1).clamp_range(RangeInclusive::new(0.0, upper)));
+            ui.end_row();
+
+            ui.label("Cycles:");
+            ui.add(egui::DragValue::new(&mut cyclic.cycles).clamp_range(RangeInclusive::new(1, MAX_CYCLES)));
+            ui.end_row();
+
+            ui.label("Speed:");
+            ui.add(units.speed().drag_value(&mut cyclic.speed).speed(0.01).clamp_range(speed_range));
+            ui.end_row();
+
+            ui.label("Failure at drop:");
+            ui.add(egui::DragValue::new(&mut cyclic.failure_drop).suffix("%").clamp_range(RangeInclusive::new(1.0, 100.0)))
+                .on_hover_text("Drop in peak force compared to the first cycle that stops the test");
+            ui.end_row();
+        });
+    }
+
+    fn test_program_ui(&mut self, ui: &mut Ui) {
+        let program = &mut self.test_parameters.program;
+        let units = self.user_preferences.units;
+        let speed_range = RangeInclusive::new(units.speed().show(MIN_CROSSHEAD_SPEED), units.speed().show(MAX_CROSSHEAD_SPEED));
+
+        let mut removed = None;
+        for (i, stage) in program.stages.iter_mut().enumerate() {
+            ui.push_id(i, |ui| {
+                egui::Grid::new("stage_grid")
+                .num_columns(2)
+                .spacing([0.0, 8.0])
+                .show(ui, |ui| {
+                    ui.label(format!("Stage {}:", i + 1));
+                    egui::ComboBox::new("stage_kind_combobox", "")
+                        .selected_text(stage.kind.to_string())
+                        .show_ui(ui, |ui| {
+                            for kind in StageKind::iter() {
+                                ui.selectable_value(&mut stage.kind, kind, kind.to_string());
+                            }
+                        });
+                    ui.end_row();
+
+                    if stage.kind == StageKind::Hold {
+                        ui.label("Duration:");
+                        ui.add(egui::DragValue::new(&mut stage.duration).suffix("s").clamp_range(RangeInclusive::new(0.0, f64::MAX)));
+                        ui.end_row();
+                    } else if stage.kind == StageKind::HoldForce {
+                        stage.limit = LimitKind::Force;
+
+                        ui.label("Force:");
+                        ui.add(units.force().drag_value(&mut stage.limit_value).speed(0.1));
+                        ui.end_row();
+
+                        ui.label("Duration:");
+                        ui.add(egui::DragValue::new(&mut stage.duration).suffix("s").clamp_range(RangeInclusive::new(0.0, f64::MAX)));
+                        ui.end_row();
+
+                        ui.label("Correction speed:");
+                        ui.add(units.speed().drag_value(&mut stage.speed).speed(0.01).clamp_range(speed_range.clone()));
+                        ui.end_row();
+                    } else {
+                        ui.label("Speed:");
+                        ui.add(units.speed().drag_value(&mut stage.speed).speed(0.01).clamp_range(speed_range.clone()));
+                        ui.end_row();
+
+                        ui.label("Until:");
+                        egui::ComboBox::new("stage_limit_combobox", "")
+                            .selected_text(stage.limit.to_string())
+                            .show_ui(ui, |ui| {
+                                for limit in LimitKind::iter() {
+                                    ui.selectable_value(&mut stage.limit, limit, limit.to_string());
+                                }
+                            });
+                        ui.end_row();
+
+                        ui.label(if stage.kind == StageKind::Move { "Reaches:" } else { "Drops to:" });
+                        ui.add(units.limit(stage.limit).drag_value(&mut stage.limit_value).speed(0.1));
+                        ui.end_row();
+                    }
+                });
+
+                if ui.small_button("Remove stage").clicked() {
+                    removed = Some(i);
+                }
+            });
+            ui.separator();
+        }
+
+        if let Some(i) = removed {
+            program.stages.remove(i);
+        }
+
+        if render_full_width(ui, button("Add stage", ButtonVariant::Secondary)).clicked() {
+            program.stages.push(Stage::default());
+        }
+    }
+
+    fn units_panel(&mut self, ui: &mut Ui) {
+        let units = &mut self.user_preferences.units;
+
+        egui::CollapsingHeader::new("Units").show(ui, |ui| {
+            egui::Frame::group(ui.style()).show(ui, |ui| {
+                units_ui(ui, "units_grid", units);
+            })
+        });
+    }
+
+    fn machine_profile_panel(&mut self, ui: &mut Ui) {
+        let units = self.user_preferences.units;
+
+        egui::CollapsingHeader::new("Machine profile").show(ui, |ui| {
+            egui::Frame::group(ui.style()).show(ui, |ui| {
+                egui::Grid::new("machine_profile_grid")
+                .num_columns(2)
+                .spacing([0.0, 8.0])
+                .show(ui, |ui| {
+                    ui.label("Name:");
+                    ui.text_edit_singleline(&mut self.machine_profile.name);
+                    ui.end_row();
+
+                    ui.label("Frame stiffness:");
+                    match self.machine_profile.compliance {
+                        Some(compliance) => ui.label(format!("{} up to {}", units.stiffness().format(compliance.stiffness(0.0), 0), units.force().format(compliance.max_force, 0))),
+                        None => ui.label("Not calibrated"),
+                    };
+                    ui.end_row();
+                });
+
+                ui.add_space(8.0);
+                ui.add_enabled(self.machine_profile.compliance.is_some(), egui::Checkbox::new(&mut self.machine_profile.correct_compliance, "Correct extension for compliance"))
+                    .on_hover_text("Applies from the next test");
+                ui.separator();
+
+                ui.label("To calibrate, run a test on a rigid specimen up to the highest force you test at.");
+                ui.add_space(8.0);
+                ui.columns(2, |columns| {
+                    if columns[0].add_enabled(!self.is_testing && !self.data_points.is_empty(), button("Calibrate", ButtonVariant::Secondary)).on_hover_text("Calibrate from the last test").clicked() {
+                        match Compliance::calibrate(&self.filtered_curve()) {
+                            Some(compliance) => self.machine_profile.compliance = Some(compliance),
+                            None => {
+                                self.toast.add(Toast {
+                                    text: "Calibration needs a test with increasing force".into(),
+                                    kind: ToastKind::Info,
+                                    options: ToastOptions::default().duration_in_seconds(3.0)
+                                });
+                            },
+                        }
+                    }
+
+                    if columns[1].add_enabled(self.machine_profile.compliance.is_some(), button("Clear", ButtonVariant::Secondary)).clicked() {
+                        self.machine_profile.compliance = None;
+                    }
+                });
+            })
+        });
+    }
+
+    fn export_panel(&mut self, ui: &mut Ui) {
+        let template_name = self.report_template().name;
+        let csv = &mut self.csv_settings;
+        let can_export = !self.data_points.is_empty() && !self.is_testing;
+        let mut export = false;
+        let mut save = false;
+        let mut report = false;
+        let mut image = None;
+
+        egui::CollapsingHeader::new("Export").show(ui, |ui| {
+            egui::Frame::group(ui.style()).show(ui, |ui| {
+                egui::Grid::new("export_grid")
+                .num_columns(2)
+                .spacing([0.0, 8.0])
+                .show(ui, |ui| {
+                    ui.label("Folder:");
+                    ui.text_edit_singleline(&mut csv.folder);
+                    ui.end_row();
+
+                    ui.label("Delimiter:");
+                    egui::ComboBox::new("csv_delimiter_combobox", "")
+                        .selected_text(csv.delimiter.to_string())
+                        .show_ui(ui, |ui| {
+                            for delimiter in Delimiter::iter() {
+                                ui.add_enabled_ui(delimiter.fits(csv.decimal_separator), |ui| {
+                                    ui.selectable_value(&mut csv.delimiter, delimiter, delimiter.to_string())
+                                        .on_disabled_hover_text(SEPARATOR_CLASH);
+                                });
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label("Decimal separator:");
+                    egui::ComboBox::new("csv_decimal_separator_combobox", "")
+                        .selected_text(csv.decimal_separator.to_string())
+                        .show_ui(ui, |ui| {
+                            for separator in DecimalSeparator::iter() {
+                                ui.add_enabled_ui(csv.delimiter.fits(separator), |ui| {
+                                    ui.selectable_value(&mut csv.decimal_separator, separator, separator.to_string())
+                                        .on_disabled_hover_text(SEPARATOR_CLASH);
+                                });
+                            }
+                        });
+                    ui.end_row();
+                });
+                // Settings saved before the clash was ruled out here.
+                if !csv.delimiter.fits(csv.decimal_separator) {
+                    ui.label(SEPARATOR_CLASH);
+                }
+
+                ui.add_space(8.0);
+                ui.checkbox(&mut csv.export_every_test, "Export every test");
+                ui.separator();
+
+                egui::Grid::new("report_export_grid")
+                .num_columns(2)
+                .spacing([0.0, 8.0])
+                .show(ui, |ui| {
+                    ui.label("Report template:");
+                    egui::ComboBox::new("report_template_combobox", "")
+                        .selected_text(template_name)
+                        .show_ui(ui, |ui| {
+                            for (i, template) in self.report_templates.iter().enumerate() {
+                                ui.selectable_value(&mut self.report_template, i, &template.name);
+                            }
+                        });
+                    ui.end_row();
+                });
+                if render_full_width(ui, button("Edit templates", ButtonVariant::Secondary)).clicked() {
+                    self.report_template_window = true;
+                }
+                ui.add_space(8.0);
+
+                ui.add_enabled_ui(can_export, |ui| {
+                    export = render_full_width(ui, button("Export CSV", ButtonVariant::Secondary)).clicked();
+                    save = render_full_width(ui, button("Save test", ButtonVariant::Secondary)).clicked();
+                    report = render_full_width(ui, button("PDF report", ButtonVariant::Secondary)).clicked();
+                });
+                ui.separator();
+
+                egui::Grid::new("plot_image_grid")
+                .num_columns(2)
+                .spacing([0.0, 8.0])
+                .show(ui, |ui| {
+                    let [width, height] = &mut self.plot_image_size;
+                    ui.label("Plot image:");
+                    ui.horizontal(|ui| {
+                        ui.add(egui::DragValue::new(width).suffix("px").speed(10.0).clamp_range(RangeInclusive::new(100, plot_image::MAX_SIZE)));
+                        ui.label("×");
+                        ui.add(egui::DragValue::new(height).suffix("px").speed(10.0).clamp_range(RangeInclusive::new(100, plot_image::MAX_SIZE)));
+                    });
+                    ui.end_row();
+                });
+                ui.columns(2, |columns| {
+                    for (column, format) in columns.iter_mut().zip(ImageFormat::iter()) {
+                        if render_full_width(column, button(&format!("Save {format}"), ButtonVariant::Secondary)).clicked() {
+                            image = Some(format);
+                        }
+                    }
+                });
+            })
+        });
+
+        if export {
+            self.export_csv();
+        }
+        if save {
+            self.save_test();
+        }
+        if report {
+            self.export_report();
+        }
+        if let Some(format) = image {
+            self.export_plot(format, ui.visuals());
+        }
+    }
+
+    fn analysis_panel(&mut self, ui: &mut Ui) {
+        egui::CollapsingHeader::new("Analysis").show(ui, |ui| {
+            egui::Frame::group(ui.style()).show(ui, |ui| {
+                egui::Grid::new("analysis_settings_grid")
+                .num_columns(2)
+                .spacing([0.0, 8.0])
+                .show(ui, |ui| {
+                    ui.label("Filter:");
+                    egui::ComboBox::new("filter_kind_combobox", "")
+                        .selected_text(self.filter_settings.kind.to_string())
+                        .show_ui(ui, |ui| {
+                            for kind in FilterKind::iter() {
+                                ui.selectable_value(&mut self.filter_settings.kind, kind, kind.to_string());
+                            }
+                        });
+                    ui.end_row();
+
+                    match self.filter_settings.kind {
+                        FilterKind::None => {},
+                        FilterKind::MovingAverage | FilterKind::Median | FilterKind::SavitzkyGolay => {
+                            ui.label("Window:");
+                            ui.add(egui::DragValue::new(&mut self.filter_settings.window).suffix(" samples").speed(0.1).clamp_range(RangeInclusive::new(3, 101)));
+                            ui.end_row();
+
+                            // Windows are centered on a sample, so they are always odd.
+                            self.filter_settings.window |= 1;
+
+                            if self.filter_settings.kind == FilterKind::SavitzkyGolay {
+                                ui.label("Order:");
+                                ui.add(egui::DragValue::new(&mut self.filter_settings.polynomial_order).speed(0.1).clamp_range(RangeInclusive::new(1, 5)));
+                                ui.end_row();
+                            }
+                        },
+                        FilterKind::LowPass => {
+                            ui.label("Cut-off:");
+                            ui.add(egui::DragValue::new(&mut self.filter_settings.cutoff_frequency).suffix("Hz").speed(0.1).clamp_range(RangeInclusive::new(0.01, 1000.0)));
+                            ui.end_row();
+                        },
+                    }
+
+                    ui.label("Modulus fit:");
+                    egui::ComboBox::new("modulus_window_combobox", "")
+                        .selected_text(self.analysis_settings.modulus_window.to_string())
+                        .show_ui(ui, |ui| {
+                            for window in FitWindow::iter() {
+                                ui.selectable_value(&mut self.analysis_settings.modulus_window, window, window.to_string());
+                            }
+                        });
+                    ui.end_row();
+
+                    if self.analysis_settings.modulus_window == FitWindow::Manual {
+                        ui.label("Strain from:");
+                        ui.add(egui::DragValue::new(&mut self.analysis_settings.modulus_strain_start).suffix("%").speed(0.01).clamp_range(RangeInclusive::new(0.0, self.analysis_settings.modulus_strain_end)));
+                        ui.end_row();
+
+                        ui.label("Strain to:");
+                        ui.add(egui::DragValue::new(&mut self.analysis_settings.modulus_strain_end).suffix("%").speed(0.01).clamp_range(RangeInclusive::new(self.analysis_settings.modulus_strain_start, 100.0)));
+                        ui.end_row();
+                    }
+
+                    ui.label("Yield offset:");
+                    ui.add(egui::DragValue::new(&mut self.analysis_settings.yield_offset).suffix("%").speed(0.01).clamp_range(RangeInclusive::new(0.0, 10.0)));
+                    ui.end_row();
+
+                    ui.label("Hardening from:");
+                    ui.add(egui::DragValue::new(&mut self.analysis_settings.hardening_strain_start).suffix("%").speed(0.1).clamp_range(RangeInclusive::new(0.0, self.analysis_settings.hardening_strain_end)));
+                    ui.end_row();
+
+                    ui.label("Hardening to:");
+                    ui.add(egui::DragValue::new(&mut self.analysis_settings.hardening_strain_end).suffix("%").speed(0.1).clamp_range(RangeInclusive::new(self.analysis_settings.hardening_strain_start, 100.0)));
+                    ui.end_row();
+                });
+            })
+        });
+    }
+
+    fn acceptance_panel(&mut self, ui: &mut Ui) {
+        let units = self.user_preferences.units;
+        let unlimited: Vec<ResultValue> = self.result_values().into_iter()
+            .filter(|result| !self.acceptance.limits.iter().any(|limit| result_key(&limit.result) == result_key(&result.name)))
+            .collect();
+        let has_reference = self.test_metadata.parameters.specimen().is_valid() && !self.curve.is_empty();
+        // Any compared curve can serve as the reference too.
+        let overlays: Vec<(usize, String)> = self.overlays.iter().enumerate()
+            .filter(|(_, overlay)| overlay.specimen.is_valid() && !overlay.curve.is_empty())
+            .map(|(i, overlay)| (i, overlay.name.clone()))
+            .collect();
+        // The reference curve is only worked out once it is picked.
+        let mut band_from_test = false;
+        let mut band_from_overlay = None;
+        let acceptance = &mut self.acceptance;
+
+        egui::CollapsingHeader::new("Acceptance").show(ui, |ui| {
+            egui::Frame::group(ui.style()).show(ui, |ui| {
+                ui.checkbox(&mut acceptance.enabled, "Check every test");
+                ui.add_space(8.0);
+
+                ui.strong("Limits");
+                let mut removed = None;
+                for (i, limit) in acceptance.limits.iter_mut().enumerate() {
+                    let scale = units.quantity(limit.quantity);
+                    ui.horizontal(|ui| {
+                        ui.label(&limit.result);
+                        if ui.small_button("✖").clicked() {
+                            removed = Some(i);
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Min:");
+                        optional_scaled_ui(ui, &mut limit.min, &scale);
+                        ui.label("Max:");
+                        optional_scaled_ui(ui, &mut limit.max, &scale);
+                    });
+                }
+                if let Some(i) = removed {
+                    acceptance.limits.remove(i);
+                }
+
+                egui::ComboBox::new("acceptance_limit_combobox", "")
+                    .selected_text("Add limit")
+                    .show_ui(ui, |ui| {
+                        for result in unlimited {
+                            if ui.selectable_label(false, &result.name).clicked() {
+                                acceptance.limits.push(ResultLimit { result: result.name, quantity: result.quantity, min: None, max: None });
+                            }
+                        }
+                    });
+                ui.add_space(8.0);
+
+                ui.strong("Tolerance band");
+                ui.horizontal(|ui| {
+                    ui.label("Tolerance:");
+                    ui.add(egui::DragValue::new(&mut acceptance.band_tolerance).prefix("± ").suffix("%").speed(0.1).clamp_range(RangeInclusive::new(0.1, 100.0)));
+                });
+                ui.horizontal(|ui| {
+                    band_from_test = ui.add_enabled(has_reference, button("Use current test", ButtonVariant::Secondary)).clicked();
+                    if acceptance.band.is_none() && ui.add(button("Enter by hand", ButtonVariant::Secondary)).clicked() {
+                        acceptance.band = Some(ToleranceBand::default());
+                    }
+                });
+                if !overlays.is_empty() {
+                    egui::ComboBox::new("tolerance_band_reference_combobox", "")
+                        .selected_text("Use compared curve")
+                        .show_ui(ui, |ui| {
+                            for (i, name) in &overlays {
+                                if ui.selectable_label(false, name).clicked() {
+                                    band_from_overlay = Some(*i);
+                                }
+                            }
+                        });
+                }
+
+                let mut clear_band = false;
+                if let Some(band) = &mut acceptance.band {
+                    ui.label(if band.reference.is_empty() { "Entered by hand".to_owned() } else { format!("Around {}", band.reference) });
+
+                    let mut removed = None;
+                    egui::Grid::new("tolerance_band_grid")
+                    .num_columns(4)
+                    .spacing([4.0, 4.0])
+                    .show(ui, |ui| {
+                        ui.label("Strain");
+                        ui.label("Lower");
+                        ui.label("Upper");
+                        ui.end_row();
+
+                        for (i, [strain, lower, upper]) in band.points.iter_mut().enumerate() {
+                            let mut percent = *strain * 100.0;
+                            if ui.add(egui::DragValue::new(&mut percent).suffix("%").speed(0.01).clamp_range(RangeInclusive::new(0.0, 1000.0))).changed() {
+                                *strain = percent / 100.0;
+                            }
+                            // Each edge stops at the other, the band never turns inside out.
+                            let stress = units.stress();
+                            let (lower_max, upper_min) = (stress.show(*upper), stress.show(*lower));
+                            ui.add(stress.drag_value(lower).speed(0.1).clamp_range(RangeInclusive::new(f64::MIN, lower_max)));
+                            ui.add(stress.drag_value(upper).speed(0.1).clamp_range(RangeInclusive::new(upper_min, f64::MAX)));
+                            if ui.small_button("✖").clicked() {
+                                removed = Some(i);
+                            }
+                            ui.end_row();
+                        }
+                    });
+                    if let Some(i) = removed {
+                        band.points.remove(i);
+                    }
+                    band.points.sort_by(|a, b| a[0].total_cmp(&b[0]));
+
+                    ui.columns(2, |columns| {
+                        if columns[0].add(button("Add point", ButtonVariant::Secondary)).clicked() {
+                            let point = band.points.last().map_or([0.0; 3], |[strain, lower, upper]| [strain + 0.01, *lower, *upper]);
+                            band.points.push(point);
+                        }
+                        clear_band = columns[1].add(button("Clear band", ButtonVariant::Secondary)).clicked();
+                    });
+                }
+                if clear_band {
+                    acceptance.band = None;
+                }
+            })
+        });
+
+        let tolerance = self.acceptance.band_tolerance;
+        if band_from_test {
+            let name = match (&self.test_metadata.info.specimen_id, self.test_metadata.date) {
+                (id, _) if !id.is_empty() => id.clone(),
+                (_, Some(date)) => date.format("%Y-%m-%d %H:%M").to_string(),
+                (_, None) => "Current test".to_owned(),
+            };
+            if let Some(curve) = self.checked_curve() {
+                self.acceptance.band = Some(ToleranceBand::around(name, &curve, tolerance));
+            }
+        }
+        if let Some(overlay) = band_from_overlay.and_then(|i| self.overlays.get(i)) {
+            if let Some(curve) = stress_strain_to_break(&overlay.curve, &overlay.specimen, &overlay.analysis) {
+                self.acceptance.band = Some(ToleranceBand::around(overlay.name.clone(), &curve, tolerance));
+            }
+        }
+    }
+
+    /// The engineering `[strain, stress]` curve of the last test up to the
+    /// break, `None` without specimen geometry.
+    fn checked_curve(&self) -> Option<Vec<[f64; 2]>> {
+        stress_strain_to_break(&self.curve, &self.test_metadata.parameters.specimen(), &self.analysis)
+    }
+
+    fn overlay_panel(&mut self, ui: &mut Ui) {
+        // Excluded specimens would only clutter the comparison.
+        let series_tests: Vec<u64> = self.series.as_ref().map_or(Vec::new(), |series| {
+            series.tests.iter().filter(|test| test.excluded.is_none()).filter_map(|test| test.history_id).filter(|id| self.history.contains(*id)).collect()
+        });
+        let mut add_series = false;
+        let mut clear = false;
+
+        egui::CollapsingHeader::new("Compare curves").show(ui, |ui| {
+            egui::Frame::group(ui.style()).show(ui, |ui| {
+                egui::Grid::new("overlay_settings_grid")
+                .num_columns(2)
+                .spacing([0.0, 8.0])
+                .show(ui, |ui| {
+                    ui.label("Align at:");
+                    egui::ComboBox::new("overlay_alignment_combobox", "")
+                        .selected_text(self.overlay_alignment.to_string())
+                        .show_ui(ui, |ui| {
+                            for alignment in Alignment::iter() {
+                                ui.selectable_value(&mut self.overlay_alignment, alignment, alignment.to_string());
+                            }
+                        });
+                    ui.end_row();
+                });
+                ui.add_space(8.0);
+
+                if self.overlays.is_empty() {
+                    ui.label("Add tests from the series, the history or the saved tests to draw them over the current test.");
+                }
+
+                let mut removed = None;
+                for (i, overlay) in self.overlays.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut overlay.visible, "");
+                        ui.color_edit_button_srgba(&mut overlay.color);
+                        ui.label(&overlay.name);
+                        if ui.small_button("✖").clicked() {
+                            removed = Some(i);
+                        }
+                    });
+                }
+                if let Some(i) = removed {
+                    self.overlays.remove(i);
+                }
+
+                ui.add_space(8.0);
+                ui.columns(2, |columns| {
+                    add_series = columns[0].add_enabled(!series_tests.is_empty(), button("Add series", ButtonVariant::Secondary)).clicked();
+                    clear = columns[1].add_enabled(!self.overlays.is_empty(), button("Clear", ButtonVariant::Secondary)).clicked();
+                });
+            })
+        });
+
+        if add_series {
+            for id in series_tests {
+                if self.overlays.iter().any(|overlay| overlay.history_id == Some(id)) {
+                    continue;
+                }
+                match self.history.load(id) {
+                    Ok(file) => self.add_overlay(file, Some(id)),
+                    Err(err) => {
+                        self.toast.add(Toast {
+                            text: err.to_string().into(),
+                            kind: ToastKind::Info,
+                            options: ToastOptions::default().duration_in_seconds(3.0)
+                        });
+                    },
+                }
+            }
+        }
+        if clear {
+            self.overlays.clear();
+        }
+    }
+
+    fn add_overlay(&mut self, test: TestFile, history_id: Option<u64>) {
+        let color = overlay::COLORS[self.overlays.len() % overlay::COLORS.len()];
+        let mut overlay = Overlay::new(test, color, &self.filter_settings, &self.analysis_settings);
+        overlay.history_id = history_id;
+        self.overlays.push(overlay);
+    }
+
+    fn results_panel(&mut self, ui: &mut Ui) {
+        ui.add_space(10.0);
+        ui.heading("Results");
+        ui.add_space(10.0);
+
+        if !self.test_metadata.parameters.specimen().is_valid() {
+            ui.label(match self.test_metadata.parameters.mode {
+                TestMode::Tension => "Set the specimen area and gauge length before the test to calculate stress and strain results.",
+                TestMode::Compression => "Set the specimen area and height before the test to calculate stress and strain results.",
+                TestMode::Flexure => "Set the support span, width and thickness before the test to calculate flexural results.",
+            });
+            ui.add_space(8.0);
+        }
+
+        egui::Grid::new("results_grid")
+        .num_columns(2)
+        .striped(true)
+        .spacing([30.0, 8.0])
+        .show(ui, |ui| {
+            for (name, value) in self.result_rows(self.user_preferences.units) {
+                ui.label(name);
+                ui.label(value.unwrap_or("-".to_owned()));
+                ui.end_row();
+            }
+        });
+
+        if let Some(verdict) = &self.verdict {
+            verdict_ui(ui, verdict);
+        }
+
+        if self.test_metadata.procedure == TestProcedure::Cyclic {
+            self.cycle_results_ui(ui);
+        }
+    }
+
+    fn cycle_results_ui(&mut self, ui: &mut Ui) {
+        let cycles = &self.analysis.cycles;
+        let force = self.user_preferences.units.force();
+        let peaks: Vec<f64> = cycles.iter().map(|cycle| cycle.peak_force).collect();
+        let failure = analysis::cycles_to_failure(&peaks, self.test_metadata.parameters.cyclic.failure_drop / 100.0);
+
+        ui.add_space(10.0);
+        ui.heading("Cycles");
+        ui.add_space(10.0);
+
+        egui::Grid::new("cycle_summary_grid")
+        .num_columns(2)
+        .striped(true)
+        .spacing([30.0, 8.0])
+        .show(ui, |ui| {
+            ui.label("Cycles recorded");
+            ui.label(cycles.len().to_string());
+            ui.end_row();
+
+            ui.label("Cycles to failure");
+            ui.label(failure.map_or("-".to_owned(), |cycle| cycle.to_string()));
+            ui.end_row();
+        });
+
+        let pages = ((cycles.len() + CYCLES_PER_PAGE - 1) / CYCLES_PER_PAGE).max(1);
+        let page = self.cycle_page.min(pages - 1);
+        let first = page * CYCLES_PER_PAGE;
+        let shown = &cycles[first..cycles.len().min(first + CYCLES_PER_PAGE)];
+
+        ui.add_space(8.0);
+        ui.horizontal(|ui| {
+            if ui.add_enabled(page > 0, egui::Button::new("<")).clicked() {
+                self.cycle_page = page - 1;
+            }
+            ui.label(format!("Page {} of {pages}", page + 1));
+            if ui.add_enabled(page + 1 < pages, egui::Button::new(">")).clicked() {
+                self.cycle_page = page + 1;
+            }
+        });
+
+        ui.add_space(8.0);
+        egui::Grid::new("cycle_results_grid")
+        .num_columns(4)
+        .striped(true)
+        .spacing([20.0, 8.0])
+        .show(ui, |ui| {
+            ui.strong("Cycle");
+            ui.strong("Peak");
+            ui.strong("Valley");
+            ui.strong("Hysteresis");
+            ui.end_row();
+
+            for (i, cycle) in shown.iter().enumerate() {
+                ui.label((first + i + 1).to_string());
+                ui.label(force.format(cycle.peak_force, 1));
+                ui.label(force.format(cycle.valley_force, 1));
+                ui.label(format!("{:.4}J", cycle.hysteresis_area));
+                ui.end_row();
+            }
+        });
+    }
+
+    fn result_rows(&self, units: Units) -> Vec<(String, Option<String>)> {
+        self.result_values().into_iter()
+            .map(|result| {
+                let value = result.value.map(|value| result.format(value, &units));
+                (result.name, value)
+            })
+            .collect()
+    }
+
+    /// Results of the last test in internal units, stress and strain are
+    /// left out without specimen geometry.
+    fn result_values(&self) -> Vec<ResultValue> {
+        let specimen = self.test_metadata.parameters.specimen();
+        let stress = |force: f64| specimen.is_valid().then_some(force / specimen.area);
+        let strain = |extension: f64| specimen.is_valid().then_some(extension / specimen.gauge_length * 100.0);
+        let yield_stress = |point: Option<[f64; 2]>| point.map(|[_, stress]| stress);
+        let results = self.analysis.results;
+        let modulus = self.analysis.modulus;
+        let hardening = self.analysis.hardening;
+        let offset = self.analysis_settings.yield_offset;
+
+        let preload = ResultValue::new("Preload", Quantity::Force, 1, self.test_metadata.preload_force);
+        let max_force = ResultValue::new("Max force (Fm)", Quantity::Force, 1, results.map(|r| r.max_force));
+        let modulus_fit = ResultValue::new("Modulus fit R²", Quantity::Number, 4, modulus.map(|fit| fit.r_squared()));
+
+        match self.test_metadata.parameters.mode {
+            TestMode::Tension => vec![
+                preload,
+                max_force,
+                ResultValue::new("Tensile strength (Rm)", Quantity::Stress, 1, results.and_then(|r| stress(r.max_force))),
+                ResultValue::new("Extension at Fm", Quantity::Length, 2, results.map(|r| r.extension_at_max_force)),
+                ResultValue::new("Elongation at Fm (Agt)", Quantity::Strain, 2, results.and_then(|r| strain(r.extension_at_max_force))),
+                ResultValue::new("Extension at break", Quantity::Length, 2, results.map(|r| r.extension_at_break)),
+                ResultValue::new("Elongation at break (At)", Quantity::Strain, 2, results.and_then(|r| strain(r.extension_at_break))),
+                ResultValue::new("Stress at break", Quantity::Stress, 1, results.and_then(|r| stress(r.force_at_break))),
+                ResultValue::new("Energy to break", Quantity::Energy, 3, results.map(|r| r.energy_to_break)),
+                ResultValue::new("Modulus (E)", Quantity::Stress, 0, modulus.map(|fit| fit.modulus())),
+                modulus_fit,
+                ResultValue::new(format!("Yield strength (Rp{offset})"), Quantity::Stress, 1, yield_stress(self.analysis.offset_yield)),
+                ResultValue::new("Upper yield (ReH)", Quantity::Stress, 1, yield_stress(self.analysis.upper_yield)),
+                ResultValue::new("Lower yield (ReL)", Quantity::Stress, 1, yield_stress(self.analysis.lower_yield)),
+                ResultValue::new("Strain hardening (n)", Quantity::Number, 3, hardening.map(|fit| fit.exponent)),
+                ResultValue::new("Strength coefficient (K)", Quantity::Stress, 0, hardening.map(|fit| fit.coefficient)),
+                ResultValue::new("Hardening fit R²", Quantity::Number, 4, hardening.map(|fit| fit.r_squared)),
+            ],
+            TestMode::Compression => vec![
+                preload,
+                max_force,
+                ResultValue::new("Compressive strength (σm)", Quantity::Stress, 1, results.and_then(|r| stress(r.max_force))),
+                ResultValue::new("Compression at Fm", Quantity::Length, 2, results.map(|r| r.extension_at_max_force)),
+                ResultValue::new("Strain at Fm", Quantity::Strain, 2, results.and_then(|r| strain(r.extension_at_max_force))),
+                ResultValue::new("Energy absorbed", Quantity::Energy, 3, results.map(|r| r.energy_to_break)),
+                ResultValue::new("Compressive modulus (Ec)", Quantity::Stress, 0, modulus.map(|fit| fit.modulus())),
+                modulus_fit,
+                ResultValue::new(format!("Compressive yield (σ{offset})"), Quantity::Stress, 1, yield_stress(self.analysis.offset_yield)),
+            ],
+            TestMode::Flexure => vec![
+                preload,
+                max_force,
+                ResultValue::new("Flexural strength (σfM)", Quantity::Stress, 1, results.and_then(|r| stress(r.max_force))),
+                ResultValue::new("Deflection at Fm", Quantity::Length, 2, results.map(|r| r.extension_at_max_force)),
+                ResultValue::new("Flexural strain at Fm (εfM)", Quantity::Strain, 2, results.and_then(|r| strain(r.extension_at_max_force))),
+                ResultValue::new("Deflection at break", Quantity::Length, 2, results.map(|r| r.extension_at_break)),
+                ResultValue::new("Flexural stress at break (σfB)", Quantity::Stress, 1, results.and_then(|r| stress(r.force_at_break))),
+                ResultValue::new("Flexural modulus (Ef)", Quantity::Stress, 0, modulus.map(|fit| fit.modulus())),
+                modulus_fit,
+            ],
+        }
+    }
+
+    /// The `[extension, force]` curve with the force filter applied.
+    fn filtered_curve(&self) -> Vec<[f64; 2]> {
+        let force = self.filter_settings.apply(&self.data_points);
+        self.data_points.iter().zip(force).map(|(s, force)| [s.extension, force]).collect()
+    }
+
+    fn analysis_inputs(&self) -> AnalysisInputs {
+        AnalysisInputs {
+            samples_version: self.samples_version,
+            filter_settings: self.filter_settings,
+            analysis_settings: self.analysis_settings,
+        }
+    }
+
+    /// Works the analysis out again when the samples or the settings changed
+    /// since the last time. During a test new samples only update the curve
+    /// until the analysis is due again.
+    fn refresh_analysis(&mut self) {
+        let inputs = self.analysis_inputs();
+        if self.analysed == Some(inputs) {
+            return;
+        }
+
+        let only_samples = self.analysed.is_some_and(|analysed| AnalysisInputs { samples_version: inputs.samples_version, ..analysed } == inputs);
+        let due = self.analysed_at.map_or(true, |at| at.elapsed().as_secs_f64() >= LIVE_ANALYSIS_INTERVAL);
+        if self.is_testing && only_samples && !due {
+            if self.curve_version != Some(self.samples_version) {
+                self.update_curve();
+            }
+            return;
+        }
+
+        self.update_analysis();
+    }
+
+    fn update_curve(&mut self) {
+        self.curve_version = Some(self.samples_version);
+        let mut curve = self.filtered_curve();
+        // The compliance the test was run with, which also holds for tests opened from a file.
+        if let Some(compliance) = self.test_metadata.compliance {
+            compliance.correct(&mut curve);
+        }
+        self.curve = curve;
+    }
+
+    fn update_analysis(&mut self) {
+        self.analysed = Some(self.analysis_inputs());
+        self.analysed_at = Some(Instant::now());
+        self.update_curve();
+        self.analysis = Analysis::new(&self.curve, &self.test_metadata.parameters.specimen(), &self.analysis_settings);
+
+        if self.test_metadata.procedure == TestProcedure::Cyclic {
+            let cycles: Vec<usize> = self.data_points.iter().map(|s| s.stage / 2).collect();
+            self.analysis.cycles = analysis::cycle_results(&self.curve, &cycles);
+        }
+    }
+
+    fn start_test(&mut self) {
+        let procedure = self.test_parameters.procedure;
+        let mode = self.test_parameters.mode;
+        let program = match procedure {
+            TestProcedure::Pull => self.test_parameters.pull_program(),
+            TestProcedure::Program => Ok(Some(self.test_parameters.program.clone())),
+            TestProcedure::Cyclic => Ok(Some(self.test_parameters.cyclic.program())),
+            TestProcedure::Relaxation => Ok(Some(self.test_parameters.relaxation.relaxation_program())),
+            TestProcedure::Creep => Ok(Some(self.test_parameters.creep.creep_program())),
+        };
+
+        let result = program.and_then(|program| match &program {
+            Some(stages) if stages.is_empty() => Err(anyhow::anyhow!("The test program has no stages")),
+            // Pushing has no break to stop it, without a limit the crosshead drives into the fixture.
+            Some(_) if mode != TestMode::Tension && self.test_parameters.max_distance <= 0.0 => {
+                Err(anyhow::anyhow!("{mode} tests need a max distance the crosshead stops at"))
+            },
+            Some(stages) => {
+                self.program_runner = Some(ProgramRunner::new(stages.clone(), mode, self.driver.values().position, self.test_parameters.max_distance));
+                Ok(program)
+            },
+            None => self.test_parameters.pull_speed()
+                .and_then(|speed| self.driver.start_test(speed))
+                .map(|_| program),
+        });
+
+        let program = match result {
+            Ok(program) => program,
+            Err(err) => {
+                self.toast.add(Toast {
+                    text: err.to_string().into(),
+                    kind: ToastKind::Info,
+                    options: ToastOptions::default().duration_in_seconds(3.0)
+                });
+                self.force_tare = None;
+                return;
+            },
+        };
+
+        self.is_testing = true;
+        self.data_points.clear();
+        self.samples_version += 1;
+        self.live_peaks = LivePeaks::default();
+        self.verdict = None;
+        self.cycle_page = 0;
+        self.sample_stride = 1;
+        self.start_data_point = None;
+        self.test_metadata = TestMetadata {
+            preload_force: self.force_tare.map(|tare| mode.direction() * (self.driver.values().force() - tare)),
+            mode,
+            procedure,
+            program,
+            date: Some(chrono::Local::now()),
+            parameters: self.test_parameters.clone(),
+            compliance: self.machine_profile.compliance.filter(|_| self.machine_profile.correct_compliance),
+            info: self.test_info.clone(),
+            cancelled: false,
+        };
+
+        self.journal = match Journal::default_path().map(|path| Journal::create(path, &self.test_file())) {
+            Some(Ok(journal)) => Some(journal),
+            Some(Err(err)) => {
+                self.toast.add(Toast {
+                    text: format!("The test isn't journaled and is lost if the app crashes: {err}").into(),
+                    kind: ToastKind::Info,
+                    options: ToastOptions::default().duration_in_seconds(3.0)
+                });
+                None
+            },
+            None => None,
+        };
+    }
+
+    /// Pulls slowly until the preload force is reached, the measured test
+    /// starts from there once the preload has been held.
+    fn start_preload(&mut self) {
+        let direction = self.test_parameters.mode.direction();
+        let target = self.driver.values().position - (direction * PRELOAD_MAX_TRAVEL * POSITION_SCALE) as f32;
+
+        match self.driver.move_to(target, self.test_parameters.preload.speed) {
+            Ok(_) => {
+                self.force_tare = Some(self.driver.values().force());
+                self.preload_phase = Some(PreloadPhase::Loading { target });
+            },
+            Err(err) => {
+                self.toast.add(Toast {
+                    text: err.to_string().into(),
+                    kind: ToastKind::Info,
+                    options: ToastOptions::default().duration_in_seconds(3.0)
+                });
+            },
+        }
+    }
+
+    fn update_preload(&mut self) {
+        let values = self.driver.values();
+        let direction = self.test_parameters.mode.direction();
+        let force = direction * (values.force() - self.force_tare.unwrap_or_default());
+
+        match self.preload_phase {
+            Some(PreloadPhase::Loading { target }) => {
+                if force >= self.test_parameters.preload.force {
+                    let _ = self.driver.stop();
+                    let hold_time = Duration::from_secs_f64(self.test_parameters.preload.hold_time);
+                    self.preload_phase = Some(PreloadPhase::Holding { until: Instant::now() + hold_time });
+                } else if direction * (values.position - target) as f64 <= 0.0 {
+                    self.preload_phase = None;
+                    self.force_tare = None;
+                    self.toast.add(Toast {
+                        text: "Preload force not reached".into(),
+                        kind: ToastKind::Info,
+                        options: ToastOptions::default().duration_in_seconds(3.0)
+                    });
+                }
+            },
+            Some(PreloadPhase::Holding { until }) => {
+                if Instant::now() >= until {
+                    self.preload_phase = None;
+                    self.start_test();
+                }
+            },
+            None => {},
+        }
+    }
+
+    fn update_data(&mut self) {
+        let opt_values = self.driver.update();
+
+        if let Some( values ) = opt_values {
+            // Only record readings that have not been recorded yet.
+            let is_new_sample = self.last_sample != Some(values.sample);
+            self.last_sample = Some(values.sample);
+
+            if self.preload_phase.is_some() {
+                self.update_preload();
+            }
+
+            if self.is_testing {
+                let x = values.position_mm();
+                let f = values.force();
+
+                // debug!("data update x:{}, f:{}", x, f);
+
+                let stage = self.program_runner.as_ref().map_or(0, |runner| runner.stage);
+
+                // Extension and force are recorded positive in the loading direction.
+                let direction = self.test_metadata.mode.direction();
+
+                if let Some((start_time, start_pos, start_force)) = self.start_data_point {
+                    if is_new_sample && values.sample % self.sample_stride == 0 {
+                        self.record(Sample {
+                            time: start_time.elapsed().as_secs_f64(),
+                            position: x,
+                            extension: direction * (start_pos - x),
+                            force: direction * (f - start_force),
+                            stage,
+                        });
+
+                        if self.data_points.len() > MAX_SAMPLES {
+                            sample::decimate(&mut self.data_points);
+                            self.samples_version += 1;
+                            self.sample_stride *= 2;
+                        }
+                    }
+                } else {
+                    // After a preload the force is zeroed before preloading, so the preload stays in the data.
+                    let start_force = self.force_tare.take().unwrap_or(f);
+                    self.start_data_point = Some((Instant::now(), x, start_force));
+                    self.record(Sample { position: x, force: direction * (f - start_force), ..Default::default() });
+                }
+    
+                if self.program_runner.is_some() {
+                    self.update_program();
+                } else if !self.driver.is_acknowledge_pending() {
+                    self.stop_test(false);
+                }
+            }
+        }
+
+        if let Some(Err(err)) = self.journal.as_mut().map(Journal::flush) {
+            self.stop_journal(err);
+        }
+    }
+
+    /// Adds a sample to the test and its journal.
+    fn record(&mut self, sample: Sample) {
+        self.data_points.push(sample);
+        self.samples_version += 1;
+        self.live_peaks.add(&sample, self.test_metadata.procedure);
+
+        if let Some(Err(err)) = self.journal.as_mut().map(|journal| journal.append(&sample)) {
+            self.stop_journal(err);
+        }
+    }
+
+    /// Gives up on journaling the running test after a write failed.
+    fn stop_journal(&mut self, err: anyhow::Error) {
+        self.journal = None;
+        self.toast.add(Toast {
+            text: format!("Journaling the test stopped: {err}").into(),
+            kind: ToastKind::Info,
+            options: ToastOptions::default().duration_in_seconds(3.0)
+        });
+    }
+
+    /// Cancels a running test and homes the crosshead.
+    fn home(&mut self) {
+        self.stop_test(true);
+        if let Err(err) = self.driver.start_home() {
+            self.toast.add(Toast {
+                text: err.to_string().into(),
+                kind: ToastKind::Info,
+                options: ToastOptions::default().duration_in_seconds(1.0)
+            });
+        }
+    }
+
+    /// Stops the test on the operator's request and homes the crosshead.
+    fn cancel_test(&mut self) {
+        self.driver.cancel_test();
+        self.stop_test(true);
+    }
+
+    /// Ends the running test, whatever stopped it: the operator, a break,
+    /// the end of the program or an error. Every way a test ends goes
+    /// through here, so none of them skips `finish_test`. `cancelled` when
+    /// the operator stopped it.
+    fn stop_test(&mut self, cancelled: bool) {
+        self.preload_phase = None;
+        self.program_runner = None;
+        if !self.is_testing {
+            return;
+        }
+
+        self.is_testing = false;
+        self.test_metadata.cancelled = cancelled;
+        // The samples of this frame aren't in the analysis yet.
+        self.update_analysis();
+        self.finish_test();
+    }
+
+    /// Runs once a test has stopped, for whatever reason.
+    ///
+    /// A test that recorded samples is complete, however it ended: it gets
+    /// a verdict, is stored in the history, added to the series and
+    /// exported like any other. A cancelled test often holds real data, a
+    /// specimen that slipped or broke early, so it is kept but marked as
+    /// cancelled and left out of the series statistics until the operator
+    /// includes it. A test stopped before its first sample leaves nothing.
+    fn finish_test(&mut self) {
+        if let Some(Err(err)) = self.journal.take().map(Journal::finish) {
+            log::warn!("Removing the test journal failed: {err}");
+        }
+
+        if self.data_points.is_empty() {
+            return;
+        }
+
+        self.verdict = self.acceptance.evaluate(&self.result_values(), self.checked_curve().as_deref(), &self.user_preferences.units);
+        if let Some(verdict) = &self.verdict {
+            self.toast.add(Toast {
+                text: format!("Test {}", verdict_status(verdict.passed).to_lowercase()).into(),
+                kind: ToastKind::Info,
+                options: ToastOptions::default().duration_in_seconds(3.0)
+            });
+        }
+
+        let mut history_id = None;
+        if !self.history.folder().as_os_str().is_empty() {
+            match self.history.add(&self.test_file()) {
+                Ok(id) => history_id = Some(id),
+                Err(err) => {
+                    self.toast.add(Toast {
+                        text: format!("Storing the test in the history failed: {err}").into(),
+                        kind: ToastKind::Info,
+                        options: ToastOptions::default().duration_in_seconds(3.0)
+                    });
+                },
+            }
+        }
+
+        if self.series.is_some() {
+            let test = SeriesTest {
+                date: self.test_metadata.date,
+                specimen_id: self.test_metadata.info.specimen_id.clone(),
+                results: self.result_values(),
+                excluded: self.test_metadata.cancelled.then(|| CANCELLED.to_owned()),
+                history_id,
+            };
+            if let Some(series) = &mut self.series {
+                series.tests.push(test);
+            }
+        }
+
+        if self.csv_settings.export_every_test {
+            self.export_csv();
+        }
+    }
+
+    /// Writes the last test to a CSV file in the export folder.
+    fn export_csv(&mut self) {
+        let date = self.test_metadata.date.unwrap_or_else(chrono::Local::now);
+        let path = std::path::Path::new(&self.csv_settings.folder).join(format!("test_{}.csv", date.format("%Y-%m-%d_%H-%M-%S")));
+
+        let result = std::fs::File::create(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|file| {
+                let mut out = std::io::BufWriter::new(file);
+                let (columns, rows) = self.csv_table();
+                self.csv_settings.write(&mut out, &self.csv_metadata(), &columns, rows.into_iter())
+            });
+
+        let text = match result {
+            Ok(_) => format!("Exported to {}", path.display()),
+            Err(err) => format!("CSV export failed: {err}"),
+        };
+        self.toast.add(Toast {
+            text: text.into(),
+            kind: ToastKind::Info,
+            options: ToastOptions::default().duration_in_seconds(3.0)
+        });
+    }
+
+    /// Writes a PDF report of the last test to the export folder.
+    fn export_report(&mut self) {
+        let date = self.test_metadata.date.unwrap_or_else(chrono::Local::now);
+        let report = self.report();
+        self.write_report(&report, &format!("report_{}.pdf", date.format("%Y-%m-%d_%H-%M-%S")));
+    }
+
+    /// Writes the plot as it is shown to an image in the export folder.
+    fn export_plot(&mut self, format: ImageFormat, visuals: &egui::Visuals) {
+        let Some(bounds) = self.plot_bounds else {
+            return;
+        };
+        let (x_label, y_label) = self.axis_labels();
+        let image = PlotImage {
+            x_label,
+            y_label,
+            bounds,
+            lines: self.plot_lines(),
+            markers: self.plot_markers(),
+            background: visuals.extreme_bg_color,
+            text_color: visuals.text_color(),
+        };
+
+        let date = self.test_metadata.date.unwrap_or_else(chrono::Local::now);
+        let path = std::path::Path::new(&self.csv_settings.folder).join(format!("plot_{}.{}", date.format("%Y-%m-%d_%H-%M-%S"), format.extension()));
+        let [width, height] = self.plot_image_size;
+
+        let result = image.encode(format, width, height)
+            .and_then(|bytes| Ok(std::fs::write(&path, bytes)?));
+
+        let text = match result {
+            Ok(_) => format!("Plot saved to {}", path.display()),
+            Err(err) => format!("Saving the plot failed: {err}"),
+        };
+        self.toast.add(Toast {
+            text: text.into(),
+            kind: ToastKind::Info,
+            options: ToastOptions::default().duration_in_seconds(3.0)
+        });
+    }
+
+    /// Writes a PDF report of the series to the export folder.
+    fn export_series_report(&mut self) {
+        let Some(report) = self.series_report() else {
+            return;
+        };
+        self.write_report(&report, &format!("series_{}.pdf", chrono::Local::now().format("%Y-%m-%d_%H-%M-%S")));
+    }
+
+    fn write_report(&mut self, report: &Report, name: &str) {
+        let path = std::path::Path::new(&self.csv_settings.folder).join(name);
+
+        let result = std::fs::File::create(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|file| report.write(&mut std::io::BufWriter::new(file)));
+
+        let text = match result {
+            Ok(_) => format!("Report written to {}", path.display()),
+            Err(err) => format!("Writing the report failed: {err}"),
+        };
+        self.toast.add(Toast {
+            text: text.into(),
+            kind: ToastKind::Info,
+            options: ToastOptions::default().duration_in_seconds(3.0)
+        });
+    }
+
+    /// The selected report template.
+    fn report_template(&self) -> ReportTemplate {
+        self.report_templates.get(self.report_template).cloned().unwrap_or_default()
+    }
+
+    /// Report of the last test, laid out by the selected template.
+    fn report(&self) -> Report {
+        let metadata = &self.test_metadata;
+        let template = self.report_template();
+        let units = template.units.unwrap_or(self.user_preferences.units);
+
+        let mut blocks: Vec<Block> = template.sections()
+            .map(|section| match section {
+                Section::Test => Block::Rows("Test".to_owned(), self.test_rows(units, template.language)),
+                Section::Specimen => Block::Rows("Specimen".to_owned(), self.specimen_rows(units)),
+                Section::Chart => Block::Chart(self.report_chart(units)),
+                Section::Results => Block::Rows("Results".to_owned(), self.result_rows(units).into_iter()
+                    .filter(|(name, _)| template.shows_result(name))
+                    .map(|(name, value)| (name, value.unwrap_or("-".to_owned())))
+                    .collect()),
+                Section::Calibration => Block::Rows("Calibration".to_owned(), calibration_rows(metadata.preload_force, metadata.compliance, units, template.language)),
+                Section::Signature => Block::Signature { operator: metadata.info.operator.clone() },
+            })
+            .collect();
+
+        // The verdict goes with the results, and only when they are in the report.
+        let results = template.sections().position(|section| section == Section::Results);
+        if let Some((i, verdict)) = results.zip(self.verdict.as_ref()) {
+            blocks.insert(i + 1, Block::Rows("Acceptance".to_owned(), verdict_rows(verdict, template.language)));
+        }
+
+        Report {
+            title: format!("{} test report", metadata.mode),
+            subtitle: metadata.date.map_or(String::new(), |date| date.format("%Y-%m-%d %H:%M").to_string()),
+            header: template.header,
+            // Without a footer of its own the report names the machine.
+            footer: if template.footer.is_empty() { self.machine_profile.name.clone() } else { template.footer },
+            language: template.language,
+            blocks,
+        }
+    }
+
+    /// Report of the series, laid out by the selected template.
+    fn series_report(&self) -> Option<Report> {
+        let series = self.series.as_ref()?;
+        let parameters = &series.parameters;
+        let template = self.report_template();
+        let units = template.units.unwrap_or(self.user_preferences.units);
+        let language = template.language;
+
+        let mut series_rows = vec![
+            ("Series".to_owned(), series.name.clone()),
+            ("Machine".to_owned(), series.machine.clone()),
+            ("Test mode".to_owned(), language.translate(&parameters.mode.to_string())),
+            ("Procedure".to_owned(), language.translate(&parameters.procedure.to_string())),
+        ];
+        series_rows.extend(speed_rows(parameters, units));
+        series_rows.push(("Specimens".to_owned(), series.tests.len().to_string()));
+        series_rows.push(("Excluded".to_owned(), series.excluded_count().to_string()));
+
+        let mut specimens = vec![["Specimen ID", "Date", "Status", "Reason"].map(str::to_owned).to_vec()];
+        specimens.extend(series.tests.iter().map(|test| vec![
+            test.specimen_id.clone(),
+            test.date.map_or(String::new(), |date| date.format("%Y-%m-%d %H:%M").to_string()),
+            language.translate(if test.excluded.is_some() { "Excluded" } else { "Valid" }),
+            test.excluded.clone().unwrap_or_default(),
+        ]));
+
+        let mut statistics = vec![["Result", "n", "Mean", "SD", "CV", "Min", "Max"].map(str::to_owned).to_vec()];
+        statistics.extend(series.statistics().into_iter()
+            .filter(|(result, _)| template.shows_result(&result.name))
+            .map(|(result, statistics)| {
+                let mut row = vec![language.translate(&result.name)];
+                match statistics {
+                    Some(s) => row.extend([
+                        s.count.to_string(),
+                        result.format(s.mean, &units),
+                        result.format(s.std_dev, &units),
+                        s.coefficient_of_variation().map_or("-".to_owned(), |cv| format!("{cv:.1}%")),
+                        result.format(s.min, &units),
+                        result.format(s.max, &units),
+                    ]),
+                    None => row.extend(["0", "-", "-", "-", "-", "-"].map(str::to_owned)),
+                }
+                row
+            }));
+
+        let preload = parameters.preload.enabled.then_some(parameters.preload.force);
+        let compliance = series.compliance;
+
+        let blocks = template.sections()
+            .filter_map(|section| match section {
+                Section::Test => Some(Block::Rows("Series".to_owned(), series_rows.clone())),
+                Section::Specimen => Some(Block::Table("Specimens".to_owned(), specimens.clone())),
+                // Only the results of the specimens are kept, not their curves.
+                Section::Chart => None,
+                Section::Results => Some(Block::Table("Results".to_owned(), statistics.clone())),
+                Section::Calibration => Some(Block::Rows("Calibration".to_owned(), calibration_rows(preload, compliance, units, language))),
+                Section::Signature => Some(Block::Signature { operator: self.test_info.operator.clone() }),
+            })
+            .collect();
+
+        Some(Report {
+            title: format!("{} series report", parameters.mode),
+            subtitle: series.name.clone(),
+            header: template.header,
+            footer: if template.footer.is_empty() { self.machine_profile.name.clone() } else { template.footer },
+            language: template.language,
+            blocks,
+        })
+    }
+
+    /// Stress–strain curve of the last test, or force–extension without
+    /// specimen geometry, with the yield points, maximum and break marked.
+    fn report_chart(&self, units: Units) -> Chart {
+        let specimen = self.test_metadata.parameters.specimen();
+        let results = self.analysis.results;
+        let mut markers = Vec::new();
+
+        if specimen.is_valid() {
+            let show = |[strain, stress]: [f64; 2]| [strain * 100.0, units.stress().show(stress)];
+            let point = |[extension, force]: [f64; 2]| show(specimen.stress_strain(&[[extension, force]])[0]);
+
+            markers.extend([
+                (format!("Rp{}", self.analysis_settings.yield_offset), self.analysis.offset_yield),
+                ("ReH".to_owned(), self.analysis.upper_yield),
+                ("ReL".to_owned(), self.analysis.lower_yield),
+            ].into_iter().filter_map(|(name, point)| Some((name, show(point?)))));
+            if let Some(results) = results {
+                markers.push(("Fm".to_owned(), point([results.extension_at_max_force, results.max_force])));
+                if results.broken {
+                    markers.push(("Break".to_owned(), point([results.extension_at_break, results.force_at_break])));
+                }
+            }
+
+            Chart {
+                x_label: "Strain (%)".to_owned(),
+                y_label: format!("Stress ({})", units.stress().symbol),
+                curve: specimen.stress_strain(&self.curve).into_iter().map(show).collect(),
+                markers,
+            }
+        } else {
+            let show = |[extension, force]: [f64; 2]| [units.length().show(extension), units.force().show(force)];
+
+            if let Some(results) = results {
+                markers.push(("Fm".to_owned(), show([results.extension_at_max_force, results.max_force])));
+                if results.broken {
+                    markers.push(("Break".to_owned(), show([results.extension_at_break, results.force_at_break])));
+                }
+            }
+
+            Chart {
+                x_label: format!("Extension ({})", units.length().symbol),
+                y_label: format!("Force ({})", units.force().symbol),
+                curve: self.curve.iter().copied().map(show).collect(),
+                markers,
+            }
+        }
+    }
+
+    /// Saves the last test to a test file in the export folder.
+    fn save_test(&mut self) {
+        let date = self.test_metadata.date.unwrap_or_else(chrono::Local::now);
+        let name = format!("test_{}.{}", date.format("%Y-%m-%d_%H-%M-%S"), test_file::EXTENSION);
+        let path = std::path::Path::new(&self.csv_settings.folder).join(name);
+
+        let text = match self.test_file().save(&path) {
+            Ok(_) => format!("Saved to {}", path.display()),
+            Err(err) => format!("Saving the test failed: {err}"),
+        };
+        self.toast.add(Toast {
+            text: text.into(),
+            kind: ToastKind::Info,
+            options: ToastOptions::default().duration_in_seconds(3.0)
+        });
+    }
+
+    /// The last test with everything needed to analyse it again.
+    fn test_file(&self) -> TestFile {
+        TestFile {
+            metadata: self.test_metadata.clone(),
+            machine_profile: self.machine_profile.clone(),
+            analysis_settings: self.analysis_settings,
+            filter_settings: self.filter_settings,
+            samples: self.data_points.clone(),
+            analysis: self.analysis.clone(),
+            verdict: self.verdict.clone(),
+        }
+    }
+
+    /// Replaces the last test with a saved one. Its parameters stay with the
+    /// test, the settings for the next test are left alone and it is
+    /// analysed with the current filter and analysis settings.
+    fn load_test(&mut self, file: TestFile) {
+        self.test_metadata = file.metadata;
+        self.data_points = file.samples;
+        self.samples_version += 1;
+        self.verdict = file.verdict;
+        self.cycle_page = 0;
+        self.follow_test = true;
+    }
+
+    fn open_test(&mut self, path: &std::path::Path) {
+        match TestFile::open(path) {
+            Ok(file) => {
+                self.load_test(file);
+                self.open_test_window = false;
+            },
+            Err(err) => {
+                self.toast.add(Toast {
+                    text: format!("Opening {} failed: {err}", path.display()).into(),
+                    kind: ToastKind::Info,
+                    options: ToastOptions::default().duration_in_seconds(3.0)
+                });
+            },
+        }
+    }
+
+    fn list_saved_tests(&mut self) {
+        self.saved_tests = test_file::list(std::path::Path::new(&self.csv_settings.folder));
+    }
+
+    /// Lists the test files in the export folder, or opens one by path.
+    fn open_test_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.open_test_window;
+        let mut selected = None;
+        let mut overlaid = None;
+        let mut refresh = false;
+
+        egui::Window::new("Open test").open(&mut open).collapsible(false).show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(format!("Tests in {}", self.csv_settings.folder));
+                refresh = ui.small_button("Refresh").clicked();
+            });
+            egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                if self.saved_tests.is_empty() {
+                    ui.label("No saved tests");
+                }
+                for path in &self.saved_tests {
+                    let name = path.file_name().map_or(String::new(), |name| name.to_string_lossy().into_owned());
+                    ui.horizontal(|ui| {
+                        if ui.selectable_label(false, name).clicked() {
+                            selected = Some(path.clone());
+                        }
+                        if ui.small_button("Overlay").on_hover_text("Draws the test over the current one").clicked() {
+                            overlaid = Some(path.clone());
+                        }
+                    });
+                }
+            });
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label("Path:");
+                ui.text_edit_singleline(&mut self.open_test_path);
+                if ui.button("Open").clicked() {
+                    selected = Some(std::path::PathBuf::from(&self.open_test_path));
+                }
+            });
+        });
+
+        self.open_test_window &= open;
+        if refresh {
+            self.list_saved_tests();
+        }
+        if let Some(path) = selected {
+            self.open_test(&path);
+        }
+        if let Some(path) = overlaid {
+            match TestFile::open(&path) {
+                Ok(file) => self.add_overlay(file, None),
+                Err(err) => {
+                    self.toast.add(Toast {
+                        text: format!("Opening {} failed: {err}", path.display()).into(),
+                        kind: ToastKind::Info,
+                        options: ToastOptions::default().duration_in_seconds(3.0)
+                    });
+                },
+            }
+        }
+    }
+
+    /// Offers to recover the test a crash interrupted.
+    fn interrupted_test_window(&mut self, ctx: &egui::Context) {
+        let mut recover = None;
+
+        egui::Window::new("Interrupted test").collapsible(false).resizable(false).show(ctx, |ui| {
+            let Some(test) = &self.interrupted_test else {
+                return;
+            };
+            let date = test.metadata.date.map_or(String::new(), |date| date.format("%Y-%m-%d %H:%M").to_string());
+
+            ui.label(format!("A {} test started {date} was interrupted, {} samples were saved.", test.metadata.mode.to_string().to_lowercase(), test.samples.len()));
+            ui.add_space(8.0);
+            ui.columns(2, |columns| {
+                if columns[0].add(button("Recover", ButtonVariant::Primary)).clicked() {
+                    recover = Some(true);
+                }
+                if columns[1].add(button("Discard", ButtonVariant::Secondary)).clicked() {
+                    recover = Some(false);
+                }
+            });
+        });
+
+        let Some(recover) = recover else {
+            return;
+        };
+
+        if let Some(mut test) = self.interrupted_test.take().filter(|_| recover) {
+            while test.samples.len() > MAX_SAMPLES {
+                sample::decimate(&mut test.samples);
+            }
+            self.load_test(test);
+            self.update_analysis();
+            // Stored and exported like any other test that finished.
+            self.finish_test();
+        }
+
+        if let Some(Err(err)) = Journal::default_path().map(std::fs::remove_file) {
+            log::warn!("Removing the test journal failed: {err}");
+        }
+    }
+
+    /// Asks whether to cancel the running test to home the crosshead.
+    fn home_confirm_window(&mut self, ctx: &egui::Context) {
+        let mut home = false;
+
+        egui::Window::new("Home").collapsible(false).resizable(false).show(ctx, |ui| {
+            ui.label("Homing cancels the running test. Cancel it and home the crosshead?");
+            ui.add_space(8.0);
+            ui.columns(2, |columns| {
+                if columns[0].add(button("Cancel test and home", ButtonVariant::Primary)).clicked() {
+                    home = true;
+                    self.home_confirm = false;
+                }
+                if columns[1].add(button("Keep testing", ButtonVariant::Secondary)).clicked() {
+                    self.home_confirm = false;
+                }
+            });
+        });
+
+        if home {
+            self.home();
+        }
+    }
+
+    /// Searchable list of the stored tests.
+    fn history_window(&mut self, ctx: &egui::Context) {
+        enum Action {
+            Open(u64),
+            Overlay(u64),
+            Archive(u64, bool),
+            Delete(u64),
+        }
+
+        let mut open = self.history_window;
+        let mut action = None;
+
+        egui::Window::new("Test history").open(&mut open).show(ctx, |ui| {
+            let filter = &mut self.history_filter;
+
+            egui::Grid::new("history_filter_grid")
+            .num_columns(2)
+            .spacing([8.0, 8.0])
+            .show(ui, |ui| {
+                ui.label("From:");
+                ui.add(egui::TextEdit::singleline(&mut filter.from).hint_text("YYYY-MM-DD"));
+                ui.end_row();
 
+                ui.label("To:");
+                ui.add(egui::TextEdit::singleline(&mut filter.to).hint_text("YYYY-MM-DD"));
+                ui.end_row();
 
-}
+                ui.label("Material:");
+                ui.text_edit_singleline(&mut filter.material);
+                ui.end_row();
 
-impl eframe::App for TensileTestingApp {
-    /// Called by the frame work to save state before shutdown.
-    fn save(&mut self, storage: &mut dyn eframe::Storage) {
-        eframe::set_value(storage, eframe::APP_KEY, &self);
-    }
-  
-    /// Called each time the UI needs repainting, which may be many times per second.
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        // debug!("ui update");
-        if ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::H)) {
-            let result = self.driver.start_home();
+                ui.label("Operator:");
+                ui.text_edit_singleline(&mut filter.operator);
+                ui.end_row();
 
-            match result {
-                Ok(_) => {},
-                Err(err) => {
-                    self.toast.add(Toast {
-                        text: err.to_string().into(),
-                        kind: ToastKind::Info,
-                        options: ToastOptions::default().duration_in_seconds(1.0)
-                    });
-                },
+                ui.label("Specimen ID:");
+                ui.text_edit_singleline(&mut filter.specimen_id);
+                ui.end_row();
+
+                ui.label("Search:");
+                ui.text_edit_singleline(&mut filter.text);
+                ui.end_row();
+            });
+            ui.checkbox(&mut filter.show_archived, "Show archived");
+            ui.separator();
+
+            egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                egui::Grid::new("history_grid")
+                .num_columns(9)
+                .striped(true)
+                .spacing([12.0, 8.0])
+                .show(ui, |ui| {
+                    for entry in self.history.entries().iter().rev().filter(|entry| filter.matches(entry)) {
+                        ui.label(entry.date.map_or(String::new(), |date| date.format("%Y-%m-%d %H:%M").to_string()));
+                        ui.label(&entry.specimen_id);
+                        ui.label(&entry.material);
+                        ui.label(entry.mode.to_string());
+                        ui.label(entry.procedure.to_string());
+                        if ui.button("Open").clicked() {
+                            action = Some(Action::Open(entry.id));
+                        }
+                        if ui.button("Overlay").on_hover_text("Draws the test over the current one").clicked() {
+                            action = Some(Action::Overlay(entry.id));
+                        }
+                        if ui.button(if entry.archived { "Restore" } else { "Archive" }).clicked() {
+                            action = Some(Action::Archive(entry.id, !entry.archived));
+                        }
+                        if ui.button("Delete").on_hover_text("Deletes the test for good").clicked() {
+                            self.history_delete = Some(entry.id);
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+        });
+
+        self.history_window &= open;
+        if !self.history_window {
+            self.history_delete = None;
+        }
+
+        if let Some(entry) = self.history_delete.and_then(|id| self.history.entries().iter().find(|entry| entry.id == id)) {
+            let name = match (&entry.specimen_id, entry.date) {
+                (id, _) if !id.is_empty() => id.clone(),
+                (_, Some(date)) => date.format("%Y-%m-%d %H:%M").to_string(),
+                (_, None) => format!("#{}", entry.id),
             };
+            let id = entry.id;
+
+            egui::Window::new("Delete test").collapsible(false).resizable(false).show(ctx, |ui| {
+                ui.label(format!("Delete test {name} from the history for good?"));
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("Delete").clicked() {
+                        action = Some(Action::Delete(id));
+                        self.history_delete = None;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        self.history_delete = None;
+                    }
+                });
+            });
+        }
+
+        let result = match action {
+            Some(Action::Open(id)) => self.history.load(id).map(|file| self.load_test(file)),
+            Some(Action::Overlay(id)) => self.history.load(id).map(|file| self.add_overlay(file, Some(id))),
+            Some(Action::Archive(id, archived)) => self.history.set_archived(id, archived),
+            Some(Action::Delete(id)) => self.delete_from_history(id),
+            None => Ok(()),
+        };
+        if let Err(err) = result {
+            self.toast.add(Toast {
+                text: err.to_string().into(),
+                kind: ToastKind::Info,
+                options: ToastOptions::default().duration_in_seconds(3.0)
+            });
+        }
+    }
+
+    /// Deletes a test from the history and forgets it wherever it was
+    /// referred to, the series keeps its results.
+    fn delete_from_history(&mut self, id: u64) -> anyhow::Result<()> {
+        self.history.delete(id)?;
+
+        for test in self.series.iter_mut().flat_map(|series| &mut series.tests).filter(|test| test.history_id == Some(id)) {
+            test.history_id = None;
+        }
+        for overlay in self.overlays.iter_mut().filter(|overlay| overlay.history_id == Some(id)) {
+            overlay.history_id = None;
         }
+        Ok(())
+    }
+
+    /// Specimens of the series and statistics of their results.
+    fn series_window(&mut self, ctx: &egui::Context) {
+        let units = self.user_preferences.units;
+        let is_testing = self.is_testing;
+        let mut open = self.series_window;
+        let mut load = None;
+        let mut report = false;
+
+        egui::Window::new("Series").open(&mut open).show(ctx, |ui| {
+            let Some(series) = &mut self.series else {
+                return;
+            };
+
+            egui::ScrollArea::vertical().max_height(600.0).show(ui, |ui| {
+                ui.strong("Specimens");
+                egui::Grid::new("series_tests_grid")
+                .num_columns(6)
+                .striped(true)
+                .spacing([12.0, 8.0])
+                .show(ui, |ui| {
+                    for heading in ["#", "Specimen ID", "Date", "Valid", "Reason", ""] {
+                        ui.strong(heading);
+                    }
+                    ui.end_row();
+
+                    for (i, test) in series.tests.iter_mut().enumerate() {
+                        ui.label((i + 1).to_string());
+                        ui.label(&test.specimen_id);
+                        ui.label(test.date.map_or(String::new(), |date| date.format("%Y-%m-%d %H:%M").to_string()));
+
+                        let mut valid = test.excluded.is_none();
+                        if ui.checkbox(&mut valid, "").on_hover_text("Counts in the statistics").changed() {
+                            test.excluded = (!valid).then(String::new);
+                        }
+                        match &mut test.excluded {
+                            Some(reason) => ui.add(egui::TextEdit::singleline(reason).hint_text("Reason").desired_width(160.0)),
+                            None => ui.label(""),
+                        };
+
+                        // The history may have lost the test since, outside of this program.
+                        match test.history_id.filter(|id| self.history.contains(*id)) {
+                            Some(id) => {
+                                if ui.add_enabled(!is_testing, egui::Button::new("Open")).clicked() {
+                                    load = Some(id);
+                                }
+                            },
+                            None => { ui.label(""); },
+                        }
+                        ui.end_row();
+                    }
+                });
+
+                ui.separator();
+                ui.strong("Statistics");
+                egui::Grid::new("series_statistics_grid")
+                .num_columns(7)
+                .striped(true)
+                .spacing([12.0, 8.0])
+                .show(ui, |ui| {
+                    for heading in ["Result", "n", "Mean", "SD", "CV", "Min", "Max"] {
+                        ui.strong(heading);
+                    }
+                    ui.end_row();
+
+                    for (result, statistics) in series.statistics() {
+                        ui.label(&result.name);
+                        match statistics {
+                            Some(s) => {
+                                ui.label(s.count.to_string());
+                                ui.label(result.format(s.mean, &units));
+                                ui.label(result.format(s.std_dev, &units));
+                                ui.label(s.coefficient_of_variation().map_or("-".to_owned(), |cv| format!("{cv:.1}%")));
+                                ui.label(result.format(s.min, &units));
+                                ui.label(result.format(s.max, &units));
+                            },
+                            None => {
+                                ui.label("0");
+                                for _ in 0..5 {
+                                    ui.label("-");
+                                }
+                            },
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
 
-        if ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowLeft)) {
-            let result = self.driver.jog(self.jog_control_step_distance);
+            ui.add_space(8.0);
+            report = render_full_width(ui, button("PDF report", ButtonVariant::Secondary)).clicked();
+        });
 
-            match result {
-                Ok(_) => {},
+        self.series_window &= open;
+        if let Some(id) = load {
+            match self.history.load(id) {
+                Ok(file) => self.load_test(file),
                 Err(err) => {
                     self.toast.add(Toast {
                         text: err.to_string().into(),
                         kind: ToastKind::Info,
-                        options: ToastOptions::default().duration_in_seconds(1.0)
+                        options: ToastOptions::default().duration_in_seconds(3.0)
                     });
                 },
+            }
+        }
+        if report {
+            self.export_series_report();
+        }
+    }
+
+    /// Lets the user add, edit and remove report templates.
+    fn report_template_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.report_template_window;
+        // Results of the current test mode, the ones a template can leave out.
+        let result_names: Vec<String> = self.result_rows(self.user_preferences.units).into_iter().map(|(name, _)| name).collect();
+        let preference_units = self.user_preferences.units;
+        let mut remove = false;
+
+        egui::Window::new("Report templates").open(&mut open).show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                egui::ComboBox::new("edit_report_template_combobox", "")
+                    .selected_text(self.report_template().name)
+                    .show_ui(ui, |ui| {
+                        for (i, template) in self.report_templates.iter().enumerate() {
+                            ui.selectable_value(&mut self.report_template, i, &template.name);
+                        }
+                    });
+                if ui.button("New").clicked() {
+                    self.report_templates.push(ReportTemplate { name: format!("Template {}", self.report_templates.len() + 1), ..Default::default() });
+                    self.report_template = self.report_templates.len() - 1;
+                }
+                if ui.button("Duplicate").clicked() {
+                    let mut template = self.report_template();
+                    template.name = format!("{} (copy)", template.name);
+                    self.report_templates.push(template);
+                    self.report_template = self.report_templates.len() - 1;
+                }
+                remove = ui.add_enabled(self.report_templates.len() > 1, egui::Button::new("Delete")).clicked();
+            });
+            ui.separator();
+
+            let Some(template) = self.report_templates.get_mut(self.report_template) else {
+                return;
             };
+
+            egui::ScrollArea::vertical().max_height(500.0).show(ui, |ui| {
+                egui::Grid::new("report_template_grid")
+                .num_columns(2)
+                .spacing([8.0, 8.0])
+                .show(ui, |ui| {
+                    ui.label("Name:");
+                    ui.text_edit_singleline(&mut template.name);
+                    ui.end_row();
+
+                    ui.label("Header:");
+                    ui.text_edit_singleline(&mut template.header);
+                    ui.end_row();
+
+                    ui.label("Footer:");
+                    ui.add(egui::TextEdit::singleline(&mut template.footer).hint_text("Machine name"));
+                    ui.end_row();
+
+                    ui.label("Language:");
+                    egui::ComboBox::new("report_language_combobox", "")
+                        .selected_text(template.language.to_string())
+                        .show_ui(ui, |ui| {
+                            for language in Language::iter() {
+                                ui.selectable_value(&mut template.language, language, language.to_string());
+                            }
+                        });
+                    ui.end_row();
+                });
+
+                ui.add_space(8.0);
+                let mut own_units = template.units.is_some();
+                if ui.checkbox(&mut own_units, "Own units").on_hover_text("Otherwise the report uses the units of the app").changed() {
+                    template.units = own_units.then_some(preference_units);
+                }
+                if let Some(units) = &mut template.units {
+                    units_ui(ui, "report_units_grid", units);
+                }
+
+                ui.separator();
+                ui.strong("Sections");
+                let mut moved = None;
+                let count = template.sections.len();
+                for (i, (section, shown)) in template.sections.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.checkbox(shown, section.to_string());
+                        if ui.add_enabled(i > 0, egui::Button::new("⏶").small()).on_hover_text("Move up").clicked() {
+                            moved = Some((i, i - 1));
+                        }
+                        if ui.add_enabled(i + 1 < count, egui::Button::new("⏷").small()).on_hover_text("Move down").clicked() {
+                            moved = Some((i, i + 1));
+                        }
+                    });
+                }
+                if let Some((from, to)) = moved {
+                    template.sections.swap(from, to);
+                }
+
+                ui.separator();
+                ui.strong("Results");
+                for name in &result_names {
+                    let mut shown = template.shows_result(name);
+                    if ui.checkbox(&mut shown, name).changed() {
+                        template.set_result_shown(name, shown);
+                    }
+                }
+            });
+        });
+
+        if remove && self.report_templates.len() > 1 {
+            self.report_templates.remove(self.report_template.min(self.report_templates.len() - 1));
+            self.report_template = self.report_template.saturating_sub(1);
         }
+        self.report_template_window &= open;
+    }
 
-        if ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowRight)) {
-            let result = self.driver.jog(-self.jog_control_step_distance);
+    /// Name–value rows describing the last test, in the chosen units.
+    fn csv_metadata(&self) -> Vec<(String, String)> {
+        let units = self.user_preferences.units;
+        let mut rows = self.test_rows(units, Language::English);
+        rows.extend(self.specimen_rows(units));
+        rows.extend(calibration_rows(self.test_metadata.preload_force, self.test_metadata.compliance, units, Language::English));
+        if let Some(verdict) = &self.verdict {
+            rows.push(("Acceptance".to_owned(), verdict_status(verdict.passed).to_owned()));
+        }
+        rows
+    }
 
-            match result {
-                Ok(_) => {},
-                Err(err) => {
+    /// When, on what and how the last test was run, test mode and
+    /// procedure in `language`.
+    fn test_rows(&self, units: Units, language: Language) -> Vec<(String, String)> {
+        let metadata = &self.test_metadata;
+        let parameters = &metadata.parameters;
+
+        let mut rows = vec![
+            ("Date".to_owned(), metadata.date.map_or(String::new(), |date| date.format("%Y-%m-%d %H:%M:%S").to_string())),
+            ("Machine".to_owned(), self.machine_profile.name.clone()),
+            ("Test mode".to_owned(), language.translate(&metadata.mode.to_string())),
+            ("Procedure".to_owned(), language.translate(&metadata.procedure.to_string())),
+        ];
+        if metadata.cancelled {
+            rows.push(("Stopped".to_owned(), language.translate(CANCELLED)));
+        }
+        rows.extend(metadata.info.rows());
+        rows.extend(speed_rows(parameters, units));
+        rows
+    }
+
+    /// Specimen geometry of the last test.
+    fn specimen_rows(&self, units: Units) -> Vec<(String, String)> {
+        let parameters = &self.test_metadata.parameters;
+        let length = |value: f64| units.length().format(value, 3);
+
+        match parameters.mode {
+            TestMode::Flexure => vec![
+                ("Support span".to_owned(), length(parameters.span)),
+                ("Width".to_owned(), length(parameters.width)),
+                ("Thickness".to_owned(), length(parameters.thickness)),
+            ],
+            mode => vec![
+                ("Specimen area".to_owned(), units.area().format(parameters.area, 3)),
+                (if mode == TestMode::Compression { "Specimen height" } else { "Gauge length" }.to_owned(), length(parameters.gauge_length)),
+            ],
+        }
+    }
+
+    /// Columns and rows of the samples, stress and strain are left out
+    /// without specimen geometry.
+    fn csv_table(&self) -> (Vec<Column>, Vec<Vec<f64>>) {
+        let units = self.user_preferences.units;
+        let specimen = self.test_metadata.parameters.specimen();
+        let compliance = self.test_metadata.compliance;
+
+        let mut columns = vec![
+            Column { name: "Time (s)".to_owned(), decimals: 3 },
+            Column { name: format!("Position ({})", units.length().symbol), decimals: 4 },
+            Column { name: format!("Extension ({})", units.length().symbol), decimals: 4 },
+            Column { name: format!("Force ({})", units.force().symbol), decimals: 3 },
+        ];
+        if specimen.is_valid() {
+            columns.push(Column { name: format!("Stress ({})", units.stress().symbol), decimals: 3 });
+            columns.push(Column { name: "Strain (%)".to_owned(), decimals: 4 });
+        }
+
+        let rows = self.data_points.iter()
+            .map(|sample| {
+                let extension = sample.extension - compliance.map_or(0.0, |c| c.deflection(sample.force));
+                let mut row = vec![
+                    sample.time,
+                    units.length().show(sample.position),
+                    units.length().show(extension),
+                    units.force().show(sample.force),
+                ];
+                if specimen.is_valid() {
+                    row.push(units.stress().show(sample.force / specimen.area));
+                    row.push(extension / specimen.gauge_length * 100.0);
+                }
+                row
+            })
+            .collect();
+
+        (columns, rows)
+    }
+
+    fn update_program(&mut self) {
+        let Some(sample) = self.data_points.last() else {
+            return;
+        };
+
+        let gauge_length = self.test_metadata.parameters.specimen().gauge_length;
+        let measurement = Measurement {
+            position: self.driver.values().position,
+            extension: sample.extension,
+            force: sample.force,
+            strain: (gauge_length > 0.0).then_some(sample.extension / gauge_length),
+        };
+
+        let Some(runner) = self.program_runner.as_mut() else {
+            return;
+        };
+
+        let result = runner.update(&mut self.driver, &measurement);
+
+        // Only whole cycles count, the one in progress has not reached its peak yet.
+        let completed_cycles = (runner.stage / 2).min(self.live_peaks.cycles.len());
+        let failed_cycle = (self.test_metadata.procedure == TestProcedure::Cyclic)
+            .then(|| analysis::cycles_to_failure(&self.live_peaks.cycles[..completed_cycles], self.test_metadata.parameters.cyclic.failure_drop / 100.0))
+            .flatten();
+
+        // Single pulls run from here have no firmware break detection.
+        let broken = self.test_metadata.procedure == TestProcedure::Pull && self.live_peaks.broken();
+
+        if broken {
+            let _ = self.driver.stop();
+            self.stop_test(false);
+            return;
+        }
+
+        if let Some(cycle) = failed_cycle {
+            let _ = self.driver.stop();
+            self.stop_test(false);
+            self.toast.add(Toast {
+                text: format!("Specimen failed in cycle {cycle}").into(),
+                kind: ToastKind::Info,
+                options: ToastOptions::default().duration_in_seconds(3.0)
+            });
+            return;
+        }
+
+        match result {
+            Ok(true) => {},
+            Ok(false) => self.stop_test(false),
+            Err(err) => {
+                let _ = self.driver.stop();
+                self.stop_test(false);
+                self.toast.add(Toast {
+                    text: err.to_string().into(),
+                    kind: ToastKind::Info,
+                    options: ToastOptions::default().duration_in_seconds(3.0)
+                });
+            },
+        }
+    }
+
+
+}
+
+impl eframe::App for TensileTestingApp {
+    /// Called by the frame work to save state before shutdown.
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, eframe::APP_KEY, &self);
+    }
+  
+    /// Called each time the UI needs repainting, which may be many times per second.
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        // debug!("ui update");
+        // Text fields get the keys while they have focus, so typing an "h"
+        // or moving the cursor doesn't move the crosshead.
+        if !ctx.wants_keyboard_input() {
+            if ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::H)) {
+                // A key is easily hit by accident, a running test isn't
+                // cancelled without asking.
+                if self.is_testing {
+                    self.home_confirm = true;
+                } else {
+                    self.home();
+                }
+            }
+
+            if ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowLeft)) {
+                let result = self.driver.jog(self.jog_control_step_distance);
+
+                match result {
+                    Ok(_) => {},
+                    Err(err) => {
                         self.toast.add(Toast {
-                        text: err.to_string().into(),
-                        kind: ToastKind::Info,
-                        options: ToastOptions::default().duration_in_seconds(1.0)
-                    });
-                },
-            };
+                            text: err.to_string().into(),
+                            kind: ToastKind::Info,
+                            options: ToastOptions::default().duration_in_seconds(1.0)
+                        });
+                    },
+                };
+            }
+
+            if ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowRight)) {
+                let result = self.driver.jog(-self.jog_control_step_distance);
+
+                match result {
+                    Ok(_) => {},
+                    Err(err) => {
+                        self.toast.add(Toast {
+                            text: err.to_string().into(),
+                            kind: ToastKind::Info,
+                            options: ToastOptions::default().duration_in_seconds(1.0)
+                        });
+                    },
+                };
+            }
         }
 
         self.update_data();
+        self.refresh_analysis();
 
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             // The top panel is often a good place for a menu bar:
@@ -478,6 +3511,27 @@ impl eframe::App for TensileTestingApp {
                 let is_web = cfg!(target_arch = "wasm32");
                 if !is_web {
                     ui.menu_button("File", |ui| {
+                        if ui.add_enabled(!self.is_testing, egui::Button::new("Open test…")).clicked() {
+                            self.open_test_window = true;
+                            self.list_saved_tests();
+                            ui.close_menu();
+                        }
+                        if ui.add_enabled(!self.is_testing, egui::Button::new("History…")).clicked() {
+                            self.history_window = true;
+                            ui.close_menu();
+                        }
+                        if ui.add_enabled(!self.data_points.is_empty() && !self.is_testing, egui::Button::new("Save test")).clicked() {
+                            self.save_test();
+                            ui.close_menu();
+                        }
+                        if ui.add_enabled(!self.data_points.is_empty() && !self.is_testing, egui::Button::new("Export CSV")).clicked() {
+                            self.export_csv();
+                            ui.close_menu();
+                        }
+                        if ui.add_enabled(!self.data_points.is_empty() && !self.is_testing, egui::Button::new("PDF report")).clicked() {
+                            self.export_report();
+                            ui.close_menu();
+                        }
                         if ui.button("Quit").clicked() {
                             ctx.send_viewport_cmd(egui::ViewportCommand::Close);
                         }
@@ -487,10 +3541,6 @@ impl eframe::App for TensileTestingApp {
             });
         });
 
-        egui::CentralPanel::default().show(ctx, |ui| {
-            self.plot_ui(ui)
-        });
-
         egui::SidePanel::new(egui::panel::Side::Right, "side_panel").show(ctx, |ui| {
             egui::ScrollArea::new([false, true]).show(ui, |ui| {
                 self.panel_ui(ui);
@@ -500,6 +3550,35 @@ impl eframe::App for TensileTestingApp {
             })
         });
 
+        egui::SidePanel::new(egui::panel::Side::Right, "results_panel").show(ctx, |ui| {
+            egui::ScrollArea::new([false, true]).show(ui, |ui| {
+                self.results_panel(ui);
+            })
+        });
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            self.plot_ui(ui)
+        });
+
+        if self.open_test_window && !self.is_testing {
+            self.open_test_window(ctx);
+        }
+        if self.history_window && !self.is_testing {
+            self.history_window(ctx);
+        }
+        if self.series_window {
+            self.series_window(ctx);
+        }
+        if self.report_template_window {
+            self.report_template_window(ctx);
+        }
+        if self.interrupted_test.is_some() {
+            self.interrupted_test_window(ctx);
+        }
+        if self.home_confirm {
+            self.home_confirm_window(ctx);
+        }
+
         // debug!("request repaint");
         // request new repaint
         ctx.request_repaint();
@@ -511,10 +3590,332 @@ impl eframe::App for TensileTestingApp {
     }
 }
 
+/// Test speed and the speed after a switch, if any.
+fn speed_rows(parameters: &TestParamters, units: Units) -> Vec<(String, String)> {
+    let mut rows = vec![("Speed".to_owned(), units.speed_unit(parameters.speed_unit).format(parameters.speed, 5))];
+
+    if parameters.rate_switch.enabled {
+        let switch = &parameters.rate_switch;
+        rows.push(("Speed after switch".to_owned(), format!(
+            "{} from {} {}",
+            units.speed_unit(switch.unit).format(switch.speed, 5),
+            switch.limit,
+            units.limit(switch.limit).format(switch.at, 3),
+        )));
+    }
+
+    rows
+}
+
+/// Preload and compliance correction a test was run with, "None" in `language`.
+fn calibration_rows(preload: Option<f64>, compliance: Option<Compliance>, units: Units, language: Language) -> Vec<(String, String)> {
+    vec![
+        ("Preload".to_owned(), preload.map_or_else(|| language.translate("None"), |force| units.force().format(force, 2))),
+        ("Compliance correction".to_owned(), match compliance {
+            Some(compliance) => format!("{} up to {}", units.stiffness().format(compliance.stiffness(0.0), 0), units.force().format(compliance.max_force, 0)),
+            None => language.translate("None"),
+        }),
+    ]
+}
+
 fn render_full_width(ui: &mut Ui, widget: impl egui::Widget) -> Response {
     ui.add_sized(egui::vec2(ui.available_width(), 0.0), widget)
 }
 
 fn is_serial_connected(connection_state: &ConnectionState) -> bool {
     matches!(&connection_state, ConnectionState::Connected)
-}
\ No newline at end of file
+}
+
+/// Returns whether the channel changed.
+fn plot_channel_ui(ui: &mut Ui, id: &str, label: &str, channel: &mut PlotChannel, has_specimen: bool) -> bool {
+    let mut changed = false;
+
+    ui.label(label);
+    egui::ComboBox::new(id, "")
+        .selected_text(channel.to_string())
+        .show_ui(ui, |ui| {
+            for c in PlotChannel::iter() {
+                ui.add_enabled_ui(has_specimen || !c.needs_specimen(), |ui| {
+                    changed |= ui.selectable_value(channel, c, c.to_string()).changed();
+                });
+            }
+        });
+
+    changed
+}
+
+fn speed_ui(ui: &mut Ui, id: &str, units: &Units, speed: &mut f64, unit: &mut SpeedUnit) {
+    ui.horizontal(|ui| {
+        let scale = units.speed_unit(*unit);
+        ui.add(egui::DragValue::from_get_set(|shown| {
+            if let Some(shown) = shown {
+                *speed = scale.store(shown);
+            }
+            scale.show(*speed)
+        }).speed(unit.drag_speed()).max_decimals(5).clamp_range(RangeInclusive::new(0.0, f64::MAX)));
+        egui::ComboBox::new(id, "")
+            .selected_text(scale.symbol.clone())
+            .show_ui(ui, |ui| {
+                for u in SpeedUnit::iter() {
+                    ui.selectable_value(unit, u, units.speed_unit(u).symbol);
+                }
+            });
+    });
+}
+
+/// Force, length and stress unit pickers.
+fn units_ui(ui: &mut Ui, id: &str, units: &mut Units) {
+    ui.push_id(id, |ui| {
+        egui::Grid::new(id)
+        .num_columns(2)
+        .spacing([0.0, 8.0])
+        .show(ui, |ui| {
+            ui.label("Force:");
+            egui::ComboBox::new("force_unit_combobox", "")
+                .selected_text(units.force.to_string())
+                .show_ui(ui, |ui| {
+                    for unit in ForceUnit::iter() {
+                        ui.selectable_value(&mut units.force, unit, unit.to_string());
+                    }
+                });
+            ui.end_row();
+
+            ui.label("Length:");
+            egui::ComboBox::new("length_unit_combobox", "")
+                .selected_text(units.length.to_string())
+                .show_ui(ui, |ui| {
+                    for unit in LengthUnit::iter() {
+                        ui.selectable_value(&mut units.length, unit, unit.to_string());
+                    }
+                });
+            ui.end_row();
+
+            ui.label("Stress:");
+            egui::ComboBox::new("stress_unit_combobox", "")
+                .selected_text(units.stress.to_string())
+                .show_ui(ui, |ui| {
+                    for unit in StressUnit::iter() {
+                        ui.selectable_value(&mut units.stress, unit, unit.to_string());
+                    }
+                });
+            ui.end_row();
+        });
+    });
+}
+
+fn hold_settings_ui(ui: &mut Ui, id: &str, units: &Units, hold: &mut HoldSettings, target: LimitKind) {
+    egui::Grid::new(id)
+    .num_columns(2)
+    .spacing([0.0, 8.0])
+    .show(ui, |ui| {
+        ui.label("Target:");
+        ui.add(units.limit(target).drag_value(&mut hold.target).speed(0.1).clamp_range(RangeInclusive::new(0.0, f64::MAX)));
+        ui.end_row();
+
+        ui.label("Loading speed:");
+        ui.add(units.speed().drag_value(&mut hold.speed).speed(0.01).clamp_range(RangeInclusive::new(units.speed().show(MIN_CROSSHEAD_SPEED), units.speed().show(MAX_CROSSHEAD_SPEED))));
+        ui.end_row();
+
+        ui.label("Hold for:");
+        ui.add(egui::DragValue::new(&mut hold.hours).suffix("h").speed(0.1).clamp_range(RangeInclusive::new(0.0, f64::MAX)));
+        ui.end_row();
+    });
+}
+
+/// A value that may be left out, edited behind a checkbox. `default` is
+/// filled in when the checkbox is ticked.
+fn optional_value_ui(ui: &mut Ui, value: &mut Option<f64>, suffix: &str, default: f64) {
+    ui.horizontal(|ui| {
+        let mut enabled = value.is_some();
+        if ui.checkbox(&mut enabled, "").changed() {
+            *value = enabled.then_some(default);
+        }
+        if let Some(value) = value {
+            ui.add(egui::DragValue::new(value).suffix(suffix).speed(0.1));
+        }
+    });
+}
+
+/// An optional internal value edited in the shown unit of `scale`, zero
+/// when the checkbox is ticked.
+fn optional_scaled_ui(ui: &mut Ui, value: &mut Option<f64>, scale: &Scale) {
+    ui.horizontal(|ui| {
+        let mut enabled = value.is_some();
+        if ui.checkbox(&mut enabled, "").changed() {
+            *value = enabled.then_some(0.0);
+        }
+        if let Some(value) = value {
+            ui.add(scale.drag_value(value).speed(0.1));
+        }
+    });
+}
+
+/// The engineering `[strain, stress]` curve of a test up to its break,
+/// `None` without specimen geometry.
+fn stress_strain_to_break(curve: &[[f64; 2]], specimen: &Specimen, analysis: &Analysis) -> Option<Vec<[f64; 2]>> {
+    if !specimen.is_valid() {
+        return None;
+    }
+
+    let end = analysis.results
+        .and_then(|results| curve.iter().position(|point| *point == [results.extension_at_break, results.force_at_break]))
+        .map_or(curve.len(), |i| i + 1);
+    Some(specimen.stress_strain(&curve[..end]))
+}
+
+/// Pass or fail of the last test and how each check turned out.
+fn verdict_ui(ui: &mut Ui, verdict: &Verdict) {
+    ui.add_space(10.0);
+    ui.heading("Acceptance");
+    ui.add_space(10.0);
+
+    let color = |passed: bool| if passed { GREEN } else { PRIMARY_COLOR };
+    ui.label(egui::RichText::new(verdict_status(verdict.passed).to_uppercase()).strong().size(18.0).color(color(verdict.passed)));
+    ui.add_space(8.0);
+
+    egui::Grid::new("verdict_grid")
+    .num_columns(2)
+    .striped(true)
+    .spacing([30.0, 8.0])
+    .show(ui, |ui| {
+        for check in &verdict.checks {
+            ui.label(egui::RichText::new(&check.name).color(color(check.passed)));
+            ui.label(&check.detail);
+            ui.end_row();
+        }
+    });
+}
+
+fn verdict_status(passed: bool) -> &'static str {
+    if passed { "Passed" } else { "Failed" }
+}
+
+/// The verdict and every check as report rows, statuses in `language`.
+fn verdict_rows(verdict: &Verdict, language: Language) -> Vec<(String, String)> {
+    let mut rows = vec![("Verdict".to_owned(), language.translate(verdict_status(verdict.passed)))];
+    rows.extend(verdict.checks.iter().map(|check| {
+        (check.name.clone(), format!("{}: {}", language.translate(verdict_status(check.passed)), check.detail))
+    }));
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cancelled_test_is_finished() {
+        let folder = std::env::temp_dir().join(format!("tensile_cancel_{}", std::process::id()));
+        let journal = folder.join("journal.jsonl");
+        let mut app = TensileTestingApp {
+            history: History::open(folder.join("history")).unwrap(),
+            ..Default::default()
+        };
+        app.journal = Some(Journal::create(journal.clone(), &app.test_file()).unwrap());
+        app.is_testing = true;
+        for i in 0..10 {
+            app.record(Sample { extension: i as f64 * 0.1, force: i as f64, ..Default::default() });
+        }
+
+        app.cancel_test();
+
+        assert!(!app.is_testing);
+        assert!(app.test_metadata.cancelled);
+        assert!(app.journal.is_none());
+        assert!(!journal.exists(), "a cancelled test must not be offered for recovery");
+        assert_eq!(app.history.entries().len(), 1);
+        std::fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn opened_test_keeps_its_own_parameters() {
+        let mut app = TensileTestingApp::default();
+        app.test_parameters.area = 10.0;
+        app.test_parameters.gauge_length = 50.0;
+        let mut file = TestFile::default();
+        file.metadata.parameters.area = 20.0;
+        file.metadata.parameters.gauge_length = 100.0;
+        file.samples = (0..10).map(|i| Sample { extension: i as f64, force: 100.0 * i as f64, ..Default::default() }).collect();
+
+        app.load_test(file);
+        app.update_analysis();
+
+        assert_eq!((app.test_parameters.area, app.test_parameters.gauge_length), (10.0, 50.0));
+        let curve = app.checked_curve().unwrap();
+        let [strain, stress] = curve[1];
+        assert_eq!((strain, stress), (0.01, 5.0));
+    }
+
+    #[test]
+    fn reports_translate_the_mode_but_not_what_was_typed_in() {
+        let mut app = TensileTestingApp::default();
+        app.test_metadata.mode = TestMode::Tension;
+        app.test_metadata.info.material = "Tension".to_owned();
+
+        let rows = app.test_rows(Units::default(), Language::German);
+        let value = |name: &str| rows.iter().find(|(row, _)| row == name).map(|(_, value)| value.as_str());
+        assert_eq!(value("Test mode"), Some("Zug"));
+        assert_eq!(value("Material"), Some("Tension"));
+    }
+
+    #[test]
+    fn analysis_is_only_redone_when_its_inputs_change() {
+        let mut app = TensileTestingApp::default();
+        for i in 0..10 {
+            app.record(Sample { extension: i as f64, force: i as f64, ..Default::default() });
+        }
+        app.refresh_analysis();
+        assert_eq!(app.curve.len(), 10);
+
+        // Nothing changed, so the cleared curve isn't worked out again.
+        app.curve.clear();
+        app.refresh_analysis();
+        assert!(app.curve.is_empty());
+
+        app.filter_settings.kind = FilterKind::MovingAverage;
+        app.refresh_analysis();
+        assert_eq!(app.curve.len(), 10);
+
+        app.curve.clear();
+        app.record(Sample { extension: 10.0, force: 10.0, ..Default::default() });
+        app.refresh_analysis();
+        assert_eq!(app.curve.len(), 11);
+    }
+
+    #[test]
+    fn running_test_analysis_waits_for_more_samples() {
+        let mut app = TensileTestingApp { is_testing: true, ..Default::default() };
+        for i in 0..10 {
+            app.record(Sample { extension: i as f64, force: 100.0 * i as f64, ..Default::default() });
+        }
+        app.refresh_analysis();
+        assert_eq!(app.analysis.results.map(|r| r.max_force), Some(900.0));
+
+        // The curve follows the new sample, the analysis isn't due yet.
+        app.record(Sample { extension: 10.0, force: 1000.0, ..Default::default() });
+        app.refresh_analysis();
+        assert_eq!(app.curve.len(), 11);
+        assert_eq!(app.analysis.results.map(|r| r.max_force), Some(900.0));
+
+        app.analysed_at = app.analysed_at.and_then(|at| at.checked_sub(Duration::from_secs_f64(LIVE_ANALYSIS_INTERVAL)));
+        app.refresh_analysis();
+        assert_eq!(app.analysis.results.map(|r| r.max_force), Some(1000.0));
+    }
+
+    #[test]
+    fn live_peaks_see_a_break_and_cycle_peaks() {
+        let mut peaks = LivePeaks::default();
+        for (force, stage) in [(50.0, 0), (100.0, 0), (20.0, 1), (80.0, 2), (60.0, 2), (10.0, 3), (6.0, 4)] {
+            peaks.add(&Sample { force, stage, ..Default::default() }, TestProcedure::Cyclic);
+        }
+
+        assert_eq!(peaks.cycles, [100.0, 80.0, 6.0]);
+        assert!(peaks.broken());
+
+        let mut peaks = LivePeaks::default();
+        peaks.add(&Sample { force: 3.0, ..Default::default() }, TestProcedure::Pull);
+        peaks.add(&Sample { force: 0.1, ..Default::default() }, TestProcedure::Pull);
+        assert!(!peaks.broken(), "too little force to count as a break");
+        assert!(peaks.cycles.is_empty());
+    }
+}
@@ -0,0 +1,252 @@
+//! Acceptance criteria for incoming inspection: limits on results and a
+//! tolerance band around a reference curve, checked after every test.
+
+use super::report::result_key;
+use super::series::ResultValue;
+use super::units::{Quantity, Units};
+
+/// Most points a band taken from a reference curve keeps.
+const MAX_BAND_POINTS: usize = 200;
+
+/// Lower and upper limit on a result, in its internal unit.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+pub struct ResultLimit {
+    /// Name of the result as it is listed in the results, matched without
+    /// its symbol so `Rp0.2` still applies after the offset changed.
+    pub result: String,
+    pub quantity: Quantity,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+/// The stress a curve has to stay between, as a function of strain.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct ToleranceBand {
+    /// The test the band was taken from, empty when it was entered by hand.
+    pub reference: String,
+    /// `[strain, lower stress, upper stress]` with strain as a fraction and
+    /// stress in MPa, by increasing strain.
+    pub points: Vec<[f64; 3]>,
+}
+
+impl ToleranceBand {
+    /// A band of ± `tolerance` % around the stress of a reference
+    /// `[strain, stress]` curve, up to its maximum strain.
+    pub fn around(reference: String, curve: &[[f64; 2]], tolerance: f64) -> Self {
+        let end = curve
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1[0].total_cmp(&b.1[0]))
+            .map_or(0, |(i, _)| i + 1);
+        let stride = end / MAX_BAND_POINTS + 1;
+
+        // Unloading or slack in the reference would fold the band back on itself.
+        let mut points: Vec<[f64; 3]> = Vec::new();
+        for [strain, stress] in curve[..end].iter().step_by(stride) {
+            if points.last().map_or(true, |[last, _, _]| strain > last) {
+                let deviation = stress.abs() * tolerance / 100.0;
+                points.push([*strain, stress - deviation, stress + deviation]);
+            }
+        }
+
+        Self { reference, points }
+    }
+
+    /// Lower and upper stress at `strain`, `None` outside the band.
+    pub fn limits(&self, strain: f64) -> Option<(f64, f64)> {
+        let i = self.points.partition_point(|[s, _, _]| *s < strain);
+        let after = self.points.get(i)?;
+        if i == 0 {
+            return (after[0] == strain).then_some((after[1], after[2]));
+        }
+
+        let before = self.points[i - 1];
+        let t = (strain - before[0]) / (after[0] - before[0]);
+        Some((before[1] + t * (after[1] - before[1]), before[2] + t * (after[2] - before[2])))
+    }
+}
+
+/// What a test is checked against.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+#[serde(default)]
+pub struct AcceptanceCriteria {
+    pub enabled: bool,
+    pub limits: Vec<ResultLimit>,
+    pub band: Option<ToleranceBand>,
+    /// ± % of stress a band taken from a reference curve spans.
+    pub band_tolerance: f64,
+}
+
+impl Default for AcceptanceCriteria {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            limits: Vec::new(),
+            band: None,
+            band_tolerance: 5.0,
+        }
+    }
+}
+
+impl AcceptanceCriteria {
+    /// Checks the results and the `[strain, stress]` curve up to the break
+    /// of a test, `curve` is `None` without specimen geometry. `None` when
+    /// there is nothing to check.
+    pub fn evaluate(&self, results: &[ResultValue], curve: Option<&[[f64; 2]]>, units: &Units) -> Option<Verdict> {
+        if !self.enabled || (self.limits.is_empty() && self.band.is_none()) {
+            return None;
+        }
+
+        let mut checks: Vec<Check> = self.limits.iter().map(|limit| limit.check(results, units)).collect();
+        if let Some(band) = &self.band {
+            checks.push(band_check(band, curve));
+        }
+
+        Some(Verdict { passed: checks.iter().all(|check| check.passed), checks })
+    }
+}
+
+impl ResultLimit {
+    fn check(&self, results: &[ResultValue], units: &Units) -> Check {
+        let scale = units.quantity(self.quantity);
+        let result = results.iter().find(|result| result_key(&result.name) == result_key(&self.result));
+        let name = result.map_or(&self.result, |result| &result.name).clone();
+        let Some(value) = result.and_then(|result| result.value) else {
+            return Check { name, passed: false, detail: "Not determined".to_owned() };
+        };
+
+        let above_min = self.min.map_or(true, |min| value >= min);
+        let below_max = self.max.map_or(true, |max| value <= max);
+        let limits = match (self.min, self.max) {
+            (Some(min), Some(max)) => format!("{} – {}", scale.format(min, 2), scale.format(max, 2)),
+            (Some(min), None) => format!("at least {}", scale.format(min, 2)),
+            (None, Some(max)) => format!("at most {}", scale.format(max, 2)),
+            (None, None) => "no limit".to_owned(),
+        };
+
+        Check {
+            name,
+            passed: above_min && below_max,
+            detail: format!("{}, required {limits}", scale.format(value, 2)),
+        }
+    }
+}
+
+fn band_check(band: &ToleranceBand, curve: Option<&[[f64; 2]]>) -> Check {
+    let name = "Tolerance band".to_owned();
+    let Some(curve) = curve else {
+        return Check { name, passed: false, detail: "Needs the specimen geometry".to_owned() };
+    };
+
+    let (mut inside, mut outside) = (0, 0);
+    for [strain, stress] in curve {
+        if let Some((lower, upper)) = band.limits(*strain) {
+            if (lower..=upper).contains(stress) {
+                inside += 1;
+            } else {
+                outside += 1;
+            }
+        }
+    }
+
+    match (inside, outside) {
+        (0, 0) => Check { name, passed: false, detail: "The curve doesn't reach the band".to_owned() },
+        (_, 0) => Check { name, passed: true, detail: "Within the band".to_owned() },
+        (inside, outside) => Check {
+            name,
+            passed: false,
+            detail: format!("{:.1}% of the curve outside the band", outside as f64 / (inside + outside) as f64 * 100.0),
+        },
+    }
+}
+
+/// The outcome of checking a test against the acceptance criteria.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+pub struct Verdict {
+    pub passed: bool,
+    pub checks: Vec<Check>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+pub struct Check {
+    pub name: String,
+    pub passed: bool,
+    /// What was measured against what was required, in the units of the time.
+    pub detail: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn band() -> ToleranceBand {
+        ToleranceBand { reference: String::new(), points: vec![[0.0, 0.0, 10.0], [0.1, 90.0, 110.0], [0.2, 100.0, 120.0]] }
+    }
+
+    #[test]
+    fn limits_interpolate_between_points() {
+        let (lower, upper) = band().limits(0.15).unwrap();
+
+        assert!((lower - 95.0).abs() < 1e-9);
+        assert!((upper - 115.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn limits_hold_on_the_points() {
+        assert_eq!(band().limits(0.1), Some((90.0, 110.0)));
+        assert_eq!(band().limits(0.2), Some((100.0, 120.0)));
+    }
+
+    #[test]
+    fn limits_start_exactly_at_the_first_point() {
+        let band = ToleranceBand { reference: String::new(), points: vec![[0.05, 40.0, 60.0], [0.1, 90.0, 110.0]] };
+
+        assert_eq!(band.limits(0.05), Some((40.0, 60.0)));
+        assert_eq!(band.limits(0.05 - 1e-12), None);
+    }
+
+    #[test]
+    fn limits_end_at_the_last_point() {
+        assert_eq!(band().limits(0.2 + 1e-12), None);
+        assert_eq!(ToleranceBand::default().limits(0.0), None);
+    }
+
+    #[test]
+    fn band_around_a_reference() {
+        let curve = [[0.0, 0.0], [0.1, 100.0], [0.2, 200.0]];
+        let band = ToleranceBand::around("Reference".to_owned(), &curve, 5.0);
+
+        assert_eq!(band.reference, "Reference");
+        assert_eq!(band.points, vec![[0.0, 0.0, 0.0], [0.1, 95.0, 105.0], [0.2, 190.0, 210.0]]);
+    }
+
+    #[test]
+    fn band_around_skips_unloading_and_stops_at_the_maximum_strain() {
+        let curve = [[0.0, 0.0], [0.1, 100.0], [0.08, 50.0], [0.2, 200.0], [0.15, 10.0]];
+        let band = ToleranceBand::around(String::new(), &curve, 10.0);
+
+        let strains: Vec<f64> = band.points.iter().map(|[strain, _, _]| *strain).collect();
+        assert_eq!(strains, vec![0.0, 0.1, 0.2]);
+    }
+
+    #[test]
+    fn band_around_keeps_long_curves_short() {
+        let curve: Vec<[f64; 2]> = (0..10_000).map(|i| [i as f64, i as f64]).collect();
+        let band = ToleranceBand::around(String::new(), &curve, 5.0);
+
+        assert!(band.points.len() <= MAX_BAND_POINTS);
+    }
+
+    #[test]
+    fn limits_match_results_without_their_symbol() {
+        let limit = ResultLimit { result: "Yield strength (Rp0.2)".to_owned(), quantity: Quantity::Stress, min: Some(200.0), max: Some(300.0) };
+        let results = |value| vec![ResultValue::new("Yield strength (Rp0.5)", Quantity::Stress, 1, Some(value))];
+
+        let check = limit.check(&results(250.0), &Units::default());
+        assert!(check.passed);
+        assert_eq!(check.name, "Yield strength (Rp0.5)");
+        assert!(!limit.check(&results(301.0), &Units::default()).passed);
+        assert!(!limit.check(&[], &Units::default()).passed);
+    }
+}
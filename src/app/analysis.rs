@@ -0,0 +1,254 @@
+//! Post-test analysis of the recorded force–extension curve.
+//!
+//! All calculations are done on the engineering stress–strain curve, with
+//! strain as a fraction (mm/mm) and stress in MPa (N/mm²).
+
+use std::fmt::{self, Formatter};
+
+use strum_macros::EnumIter;
+
+mod cyclic;
+mod modulus;
+mod results;
+mod strain_hardening;
+mod yield_strength;
+
+pub use cyclic::*;
+pub use modulus::*;
+pub use results::*;
+pub use strain_hardening::*;
+pub use yield_strength::*;
+
+/// Specimen geometry needed to convert force–extension into stress–strain.
+#[derive(Debug, Clone, Copy)]
+pub struct Specimen {
+    /// Cross-sectional area in mm².
+    pub area: f64,
+    /// Initial gauge length in mm.
+    pub gauge_length: f64,
+}
+
+impl Specimen {
+    /// A three point bend specimen, with `span` between the supports and a
+    /// `width` × `thickness` cross-section, all in mm. Flexural stress
+    /// `3FL / 2bd²` and strain `6Dd / L²` are linear in force and
+    /// deflection, so they map onto an equivalent area and gauge length.
+    pub fn three_point_bend(span: f64, width: f64, thickness: f64) -> Self {
+        if span <= 0.0 || width <= 0.0 || thickness <= 0.0 {
+            return Self { area: 0.0, gauge_length: 0.0 };
+        }
+
+        Self {
+            area: 2.0 * width * thickness.powi(2) / (3.0 * span),
+            gauge_length: span.powi(2) / (6.0 * thickness),
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.area > 0.0 && self.gauge_length > 0.0
+    }
+
+    /// Converts `[extension, force]` samples into `[strain, stress]` points.
+    pub fn stress_strain(&self, data_points: &[[f64; 2]]) -> Vec<[f64; 2]> {
+        data_points
+            .iter()
+            .map(|[extension, force]| [extension / self.gauge_length, force / self.area])
+            .collect()
+    }
+
+    /// Maps a `[strain, stress]` point back onto the force–extension plot.
+    pub fn force_extension(&self, [strain, stress]: [f64; 2]) -> [f64; 2] {
+        [strain * self.gauge_length, stress * self.area]
+    }
+}
+
+/// Least squares fit of `y = slope * x + intercept`.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy)]
+pub struct LinearFit {
+    pub slope: f64,
+    pub intercept: f64,
+    pub r_squared: f64,
+}
+
+impl LinearFit {
+    pub fn from_points(points: &[[f64; 2]]) -> Option<Self> {
+        let mut sums = RegressionSums::default();
+        points.iter().for_each(|p| sums.add(*p));
+        sums.fit()
+    }
+
+    pub fn y(&self, x: f64) -> f64 {
+        self.slope * x + self.intercept
+    }
+
+    pub fn x(&self, y: f64) -> f64 {
+        (y - self.intercept) / self.slope
+    }
+}
+
+/// Running sums for a linear regression, so a fit window can slide over a
+/// curve without summing every window from scratch.
+#[derive(Debug, Default, Clone, Copy)]
+struct RegressionSums {
+    n: f64,
+    x: f64,
+    y: f64,
+    xx: f64,
+    xy: f64,
+    yy: f64,
+}
+
+impl RegressionSums {
+    fn add(&mut self, [x, y]: [f64; 2]) {
+        self.n += 1.0;
+        self.x += x;
+        self.y += y;
+        self.xx += x * x;
+        self.xy += x * y;
+        self.yy += y * y;
+    }
+
+    fn remove(&mut self, [x, y]: [f64; 2]) {
+        self.n -= 1.0;
+        self.x -= x;
+        self.y -= y;
+        self.xx -= x * x;
+        self.xy -= x * y;
+        self.yy -= y * y;
+    }
+
+    fn fit(&self) -> Option<LinearFit> {
+        if self.n < 2.0 {
+            return None;
+        }
+
+        let var_x = self.n * self.xx - self.x * self.x;
+        let var_y = self.n * self.yy - self.y * self.y;
+        let cov = self.n * self.xy - self.x * self.y;
+
+        if var_x <= f64::EPSILON {
+            return None;
+        }
+
+        let slope = cov / var_x;
+        let intercept = (self.y - slope * self.x) / self.n;
+        let r_squared = if var_y <= f64::EPSILON {
+            1.0
+        } else {
+            (cov * cov / (var_x * var_y)).min(1.0)
+        };
+
+        Some(LinearFit {
+            slope,
+            intercept,
+            r_squared,
+        })
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, EnumIter)]
+pub enum FitWindow {
+    Automatic,
+    Manual,
+}
+
+impl fmt::Display for FitWindow {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            FitWindow::Automatic => write!(f, "Automatic"),
+            FitWindow::Manual => write!(f, "Manual"),
+        }
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct AnalysisSettings {
+    pub modulus_window: FitWindow,
+    /// Start of the manual modulus fit range, in % strain.
+    pub modulus_strain_start: f64,
+    /// End of the manual modulus fit range, in % strain.
+    pub modulus_strain_end: f64,
+    /// Strain offset for the offset yield strength, in %.
+    pub yield_offset: f64,
+    /// Start of the strain hardening fit range, in % engineering strain.
+    pub hardening_strain_start: f64,
+    /// End of the strain hardening fit range, in % engineering strain.
+    pub hardening_strain_end: f64,
+}
+
+impl Default for AnalysisSettings {
+    fn default() -> Self {
+        Self {
+            modulus_window: FitWindow::Automatic,
+            modulus_strain_start: 0.05,
+            modulus_strain_end: 0.25,
+            yield_offset: 0.2,
+            hardening_strain_start: 10.0,
+            hardening_strain_end: 20.0,
+        }
+    }
+}
+
+/// Everything derived from a single test curve.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct Analysis {
+    pub results: Option<TensileResults>,
+    pub modulus: Option<ModulusFit>,
+    /// Strain offset used for `offset_yield`, as a fraction.
+    pub yield_offset: f64,
+    pub offset_yield: Option<[f64; 2]>,
+    pub upper_yield: Option<[f64; 2]>,
+    pub lower_yield: Option<[f64; 2]>,
+    pub hardening: Option<HardeningFit>,
+    /// Per cycle results of a cyclic test.
+    pub cycles: Vec<CycleResult>,
+}
+
+impl Analysis {
+    /// Analyses `[extension, force]` samples. Anything based on stress or
+    /// strain is left out when the specimen geometry is not set.
+    pub fn new(data_points: &[[f64; 2]], specimen: &Specimen, settings: &AnalysisSettings) -> Self {
+        let results = TensileResults::new(data_points);
+
+        if !specimen.is_valid() {
+            return Self {
+                results,
+                ..Default::default()
+            };
+        }
+
+        let stress_strain = &specimen.stress_strain(data_points);
+        let modulus = match settings.modulus_window {
+            FitWindow::Automatic => ModulusFit::automatic(stress_strain),
+            FitWindow::Manual => ModulusFit::in_range(
+                stress_strain,
+                settings.modulus_strain_start / 100.0,
+                settings.modulus_strain_end / 100.0,
+            ),
+        };
+
+        let yield_offset = settings.yield_offset / 100.0;
+        let offset_yield = modulus
+            .as_ref()
+            .and_then(|modulus| offset_yield(stress_strain, modulus, yield_offset));
+        let plateau = yield_plateau(stress_strain);
+        let hardening = HardeningFit::new(
+            stress_strain,
+            settings.hardening_strain_start / 100.0,
+            settings.hardening_strain_end / 100.0,
+        );
+
+        Self {
+            results,
+            modulus,
+            yield_offset,
+            offset_yield,
+            upper_yield: plateau.map(|(upper, _)| upper),
+            lower_yield: plateau.map(|(_, lower)| lower),
+            hardening,
+            cycles: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,97 @@
+/// Results of a single load–unload cycle.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy)]
+pub struct CycleResult {
+    /// Highest force in the cycle in N.
+    pub peak_force: f64,
+    /// Lowest force in the cycle in N.
+    pub valley_force: f64,
+    /// Area enclosed by the force–extension loop, the energy lost in the cycle, in J.
+    pub hysteresis_area: f64,
+}
+
+/// Splits an `[extension, force]` curve into cycles, `cycles[i]` is the cycle
+/// point `i` was recorded in.
+pub fn cycle_results(curve: &[[f64; 2]], cycles: &[usize]) -> Vec<CycleResult> {
+    let mut results = Vec::new();
+    let mut start = 0;
+
+    while start < curve.len().min(cycles.len()) {
+        let cycle = cycles[start];
+        let end = start + cycles[start..].iter().take_while(|&&c| c == cycle).count();
+        let points = &curve[start..end];
+
+        results.push(CycleResult {
+            peak_force: points.iter().map(|p| p[1]).fold(f64::MIN, f64::max),
+            valley_force: points.iter().map(|p| p[1]).fold(f64::MAX, f64::min),
+            hysteresis_area: loop_area(points) / 1000.0,
+        });
+
+        start = end;
+    }
+
+    results
+}
+
+/// The first cycle, counting from one, whose peak force dropped more than
+/// `failure_drop` (a fraction) below the peak force of the first cycle.
+pub fn cycles_to_failure(peak_forces: &[f64], failure_drop: f64) -> Option<usize> {
+    let reference = *peak_forces.first()?;
+
+    peak_forces
+        .iter()
+        .position(|peak| *peak < reference * (1.0 - failure_drop))
+        .map(|i| i + 1)
+}
+
+/// Area of the polygon closed between the first and last point (shoelace formula).
+fn loop_area(points: &[[f64; 2]]) -> f64 {
+    let Some(first) = points.first() else {
+        return 0.0;
+    };
+
+    points
+        .iter()
+        .zip(points.iter().skip(1).chain(std::iter::once(first)))
+        .map(|(a, b)| a[0] * b[1] - b[0] * a[1])
+        .sum::<f64>()
+        .abs()
+        / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loop_area_of_a_rectangle() {
+        assert_eq!(loop_area(&[[0.0, 0.0], [2.0, 0.0], [2.0, 100.0], [0.0, 100.0]]), 200.0);
+    }
+
+    #[test]
+    fn splits_the_curve_into_cycles() {
+        // A 2 mm by 100 N rectangle, then a loop without area.
+        let curve = [[0.0, 0.0], [2.0, 0.0], [2.0, 100.0], [0.0, 100.0], [0.0, 10.0], [1.0, 50.0]];
+        let results = cycle_results(&curve, &[0, 0, 0, 0, 1, 1]);
+
+        assert_eq!(results.len(), 2);
+        assert_eq!((results[0].peak_force, results[0].valley_force), (100.0, 0.0));
+        assert_eq!(results[0].hysteresis_area, 0.2);
+        assert_eq!((results[1].peak_force, results[1].valley_force), (50.0, 10.0));
+        assert_eq!(results[1].hysteresis_area, 0.0);
+    }
+
+    #[test]
+    fn failure_on_the_cycle_the_peak_drops() {
+        let peaks = [100.0, 95.0, 85.0, 75.0];
+
+        assert_eq!(cycles_to_failure(&peaks, 0.2), Some(4));
+        assert_eq!(cycles_to_failure(&peaks, 0.5), None);
+    }
+
+    #[test]
+    fn empty_curve_has_no_cycles() {
+        assert!(cycle_results(&[], &[]).is_empty());
+        assert_eq!(cycles_to_failure(&[], 0.2), None);
+        assert_eq!(loop_area(&[]), 0.0);
+    }
+}
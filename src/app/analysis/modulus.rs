@@ -0,0 +1,151 @@
+use super::{LinearFit, RegressionSums};
+
+/// Fraction of the peak stress an automatic fit window has to span.
+const AUTO_WINDOW_STRESS_SPAN: f64 = 0.2;
+/// Windows fitting worse than this are only used when no window fits better.
+const MIN_R_SQUARED: f64 = 0.995;
+const MIN_FIT_POINTS: usize = 5;
+
+/// Young's modulus from a linear fit of the elastic part of the curve.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy)]
+pub struct ModulusFit {
+    /// Fit of stress (MPa) against strain, the slope is the modulus in MPa.
+    pub line: LinearFit,
+    pub strain_start: f64,
+    pub strain_end: f64,
+}
+
+impl ModulusFit {
+    pub fn modulus(&self) -> f64 {
+        self.line.slope
+    }
+
+    pub fn r_squared(&self) -> f64 {
+        self.line.r_squared
+    }
+
+    /// Fits all points with a strain between `strain_start` and `strain_end`.
+    pub fn in_range(stress_strain: &[[f64; 2]], strain_start: f64, strain_end: f64) -> Option<Self> {
+        let window: Vec<[f64; 2]> = stress_strain
+            .iter()
+            .copied()
+            .filter(|[strain, _]| (strain_start..=strain_end).contains(strain))
+            .collect();
+
+        if window.len() < MIN_FIT_POINTS {
+            return None;
+        }
+
+        let line = LinearFit::from_points(&window).filter(|line| line.slope > 0.0)?;
+
+        Some(Self {
+            line,
+            strain_start,
+            strain_end,
+        })
+    }
+
+    /// Slides a window spanning a fixed part of the peak stress over the curve
+    /// up to the peak and keeps the steepest window that is still linear.
+    pub fn automatic(stress_strain: &[[f64; 2]]) -> Option<Self> {
+        let (peak, peak_stress) = stress_strain
+            .iter()
+            .enumerate()
+            .map(|(i, [_, stress])| (i, *stress))
+            .max_by(|a, b| a.1.total_cmp(&b.1))?;
+
+        if peak_stress <= 0.0 {
+            return None;
+        }
+
+        let curve = &stress_strain[..=peak];
+        let span = peak_stress * AUTO_WINDOW_STRESS_SPAN;
+        let covers_span = |start: usize, end: usize| {
+            end - start >= MIN_FIT_POINTS && curve[end - 1][1] - curve[start][1] >= span
+        };
+
+        let mut sums = RegressionSums::default();
+        let mut end = 0;
+        let mut best: Option<Self> = None;
+
+        for start in 0..curve.len() {
+            while end < curve.len() && !covers_span(start, end) {
+                sums.add(curve[end]);
+                end += 1;
+            }
+
+            if !covers_span(start, end) {
+                break;
+            }
+
+            if let Some(line) = sums.fit().filter(|line| line.slope > 0.0) {
+                let candidate = Self {
+                    line,
+                    strain_start: curve[start][0],
+                    strain_end: curve[end - 1][0],
+                };
+
+                if best.map_or(true, |best| candidate.is_better_than(&best)) {
+                    best = Some(candidate);
+                }
+            }
+
+            sums.remove(curve[start]);
+        }
+
+        best
+    }
+
+    fn is_better_than(&self, other: &Self) -> bool {
+        match (self.r_squared() >= MIN_R_SQUARED, other.r_squared() >= MIN_R_SQUARED) {
+            (true, false) => true,
+            (false, true) => false,
+            (true, true) => self.modulus() > other.modulus(),
+            (false, false) => self.r_squared() > other.r_squared(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Elastic up to 200 MPa at 200 GPa, then hardening at 1 GPa.
+    fn bilinear_curve() -> Vec<[f64; 2]> {
+        (0..=100)
+            .map(|i| {
+                let strain = i as f64 * 0.0001;
+                let stress = if strain <= 0.001 { 200_000.0 * strain } else { 200.0 + 1000.0 * (strain - 0.001) };
+                [strain, stress]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn in_range_fits_the_window() {
+        let fit = ModulusFit::in_range(&bilinear_curve(), 0.0002, 0.0008).unwrap();
+
+        assert!((fit.modulus() - 200_000.0).abs() < 1e-6);
+        assert!(fit.line.intercept.abs() < 1e-9);
+        assert!((fit.r_squared() - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn in_range_needs_enough_points() {
+        assert!(ModulusFit::in_range(&bilinear_curve(), 0.0002, 0.0004).is_none());
+    }
+
+    #[test]
+    fn automatic_finds_the_elastic_part() {
+        let fit = ModulusFit::automatic(&bilinear_curve()).unwrap();
+
+        assert!((fit.modulus() - 200_000.0).abs() < 1e-6);
+        assert!(fit.strain_end <= 0.001 + 1e-12);
+    }
+
+    #[test]
+    fn automatic_rejects_unloaded_curves() {
+        assert!(ModulusFit::automatic(&[]).is_none());
+        assert!(ModulusFit::automatic(&[[0.0, 0.0], [0.1, -1.0]]).is_none());
+    }
+}
@@ -0,0 +1,92 @@
+/// Force drop, as a fraction of the maximum force, that marks the break.
+pub const BREAK_FORCE_FRACTION: f64 = 0.1;
+
+/// Standard tensile results read straight from the force–extension curve.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy)]
+pub struct TensileResults {
+    /// Maximum force (Fm) in N.
+    pub max_force: f64,
+    pub extension_at_max_force: f64,
+    pub force_at_break: f64,
+    pub extension_at_break: f64,
+    /// Area under the force–extension curve up to the break, in J.
+    pub energy_to_break: f64,
+    /// Whether the force has collapsed after the maximum.
+    pub broken: bool,
+}
+
+impl TensileResults {
+    pub fn new(data_points: &[[f64; 2]]) -> Option<Self> {
+        let (max_index, [extension_at_max_force, max_force]) = data_points
+            .iter()
+            .copied()
+            .enumerate()
+            .max_by(|a, b| a.1[1].total_cmp(&b.1[1]))?;
+
+        if max_force <= 0.0 {
+            return None;
+        }
+
+        let broken_index = break_index(data_points, max_index, max_force);
+        let broken = broken_index.is_some();
+        let break_index = broken_index.unwrap_or(data_points.len() - 1);
+        let [extension_at_break, force_at_break] = data_points[break_index];
+
+        let energy_to_break = data_points[..=break_index]
+            .windows(2)
+            .map(|pair| (pair[1][0] - pair[0][0]) * (pair[0][1] + pair[1][1]) / 2.0)
+            .sum::<f64>()
+            / 1000.0;
+
+        Some(Self {
+            max_force,
+            extension_at_max_force,
+            force_at_break,
+            extension_at_break,
+            energy_to_break,
+            broken,
+        })
+    }
+}
+
+/// The last sample before the force collapses after the maximum, `None` when
+/// the specimen has not broken (yet).
+fn break_index(data_points: &[[f64; 2]], max_index: usize, max_force: f64) -> Option<usize> {
+    data_points[max_index..]
+        .iter()
+        .position(|[_, force]| *force < max_force * BREAK_FORCE_FRACTION)
+        .map(|i| max_index + i - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn break_is_the_last_sample_above_a_tenth_of_fm() {
+        let curve = [[0.0, 0.0], [1.0, 100.0], [2.0, 200.0], [3.0, 150.0], [4.0, 19.0], [5.0, 0.0]];
+        let results = TensileResults::new(&curve).unwrap();
+
+        assert!(results.broken);
+        assert_eq!((results.max_force, results.extension_at_max_force), (200.0, 2.0));
+        assert_eq!((results.extension_at_break, results.force_at_break), (3.0, 150.0));
+        // 50 + 150 + 175 N·mm up to the break.
+        assert!((results.energy_to_break - 0.375).abs() < 1e-12);
+    }
+
+    #[test]
+    fn unbroken_curve_ends_at_the_last_sample() {
+        let curve = [[0.0, 0.0], [1.0, 100.0], [2.0, 200.0], [3.0, 21.0]];
+        let results = TensileResults::new(&curve).unwrap();
+
+        assert!(!results.broken);
+        assert_eq!((results.extension_at_break, results.force_at_break), (3.0, 21.0));
+        assert!((results.energy_to_break - 0.3105).abs() < 1e-12);
+    }
+
+    #[test]
+    fn no_results_without_force() {
+        assert!(TensileResults::new(&[]).is_none());
+        assert!(TensileResults::new(&[[0.0, 0.0], [1.0, -5.0]]).is_none());
+    }
+}
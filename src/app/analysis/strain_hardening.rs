@@ -0,0 +1,104 @@
+use super::LinearFit;
+
+const MIN_FIT_POINTS: usize = 5;
+
+/// Converts an engineering `[strain, stress]` point to true strain and stress.
+/// Only valid while deformation is uniform, i.e. up to the maximum force.
+pub fn true_stress_strain([strain, stress]: [f64; 2]) -> [f64; 2] {
+    [strain.ln_1p(), stress * (1.0 + strain)]
+}
+
+/// The true stress–strain curve up to the maximum stress, beyond which the
+/// specimen necks and the conversion no longer holds.
+pub fn true_curve(stress_strain: &[[f64; 2]]) -> Vec<[f64; 2]> {
+    let peak = stress_strain
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1[1].total_cmp(&b.1[1]))
+        .map_or(0, |(i, _)| i + 1);
+
+    stress_strain[..peak].iter().copied().map(true_stress_strain).collect()
+}
+
+/// Hollomon fit `σ = K·εⁿ` of the true stress–strain curve.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy)]
+pub struct HardeningFit {
+    /// Strain hardening exponent n.
+    pub exponent: f64,
+    /// Strength coefficient K in MPa.
+    pub coefficient: f64,
+    /// Goodness of the fit in log–log space.
+    pub r_squared: f64,
+}
+
+impl HardeningFit {
+    /// Fits the points with an engineering strain between `strain_start` and
+    /// `strain_end`, limited to the uniform part of the curve.
+    pub fn new(stress_strain: &[[f64; 2]], strain_start: f64, strain_end: f64) -> Option<Self> {
+        let log_points: Vec<[f64; 2]> = true_curve(stress_strain)
+            .into_iter()
+            .filter(|[true_strain, true_stress]| {
+                let strain = true_strain.exp_m1();
+                (strain_start..=strain_end).contains(&strain) && *true_strain > 0.0 && *true_stress > 0.0
+            })
+            .map(|[true_strain, true_stress]| [true_strain.ln(), true_stress.ln()])
+            .collect();
+
+        if log_points.len() < MIN_FIT_POINTS {
+            return None;
+        }
+
+        let line = LinearFit::from_points(&log_points)?;
+
+        Some(Self {
+            exponent: line.slope,
+            coefficient: line.intercept.exp(),
+            r_squared: line.r_squared,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn true_stress_strain_conserves_volume() {
+        let [true_strain, true_stress] = true_stress_strain([0.1, 400.0]);
+
+        assert!((true_strain - 1.1_f64.ln()).abs() < 1e-12);
+        assert!((true_stress - 440.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn true_curve_stops_at_the_peak() {
+        let curve = [[0.0, 0.0], [0.1, 300.0], [0.2, 400.0], [0.3, 350.0]];
+
+        assert_eq!(true_curve(&curve).len(), 3);
+    }
+
+    #[test]
+    fn hollomon_fit_recovers_exponent_and_coefficient() {
+        // σ = 500·ε^0.2 in true stress and strain, necking starts at ε = n.
+        let curve: Vec<[f64; 2]> = (1..=150)
+            .map(|i| {
+                let true_strain = i as f64 * 0.001;
+                let strain = true_strain.exp_m1();
+                [strain, 500.0 * true_strain.powf(0.2) / (1.0 + strain)]
+            })
+            .collect();
+
+        let fit = HardeningFit::new(&curve, 0.02, 0.2).unwrap();
+
+        assert!((fit.exponent - 0.2).abs() < 1e-9);
+        assert!((fit.coefficient - 500.0).abs() < 1e-6);
+        assert!((fit.r_squared - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn hollomon_fit_needs_enough_points() {
+        let curve = [[0.01, 100.0], [0.02, 150.0], [0.03, 180.0]];
+
+        assert!(HardeningFit::new(&curve, 0.0, 1.0).is_none());
+    }
+}
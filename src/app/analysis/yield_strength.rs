@@ -0,0 +1,133 @@
+use super::ModulusFit;
+
+/// Drop in stress after a local maximum that counts as a yield drop, as a
+/// fraction of the peak stress so noise at low loads is not picked up.
+const YIELD_DROP_FRACTION: f64 = 0.01;
+
+/// Offset yield strength (Rp): where the curve crosses the modulus line
+/// shifted by `offset` strain. Returns the `[strain, stress]` intersection.
+pub fn offset_yield(stress_strain: &[[f64; 2]], modulus: &ModulusFit, offset: f64) -> Option<[f64; 2]> {
+    let offset_line = |strain: f64| modulus.line.y(strain - offset);
+    let above_line = |[strain, stress]: [f64; 2]| stress - offset_line(strain);
+
+    let start = stress_strain
+        .iter()
+        .position(|[strain, _]| *strain >= modulus.strain_start)?;
+
+    stress_strain[start..].windows(2).find_map(|pair| {
+        let (a, b) = (above_line(pair[0]), above_line(pair[1]));
+
+        if a > 0.0 && b <= 0.0 {
+            let t = a / (a - b);
+            Some([
+                pair[0][0] + t * (pair[1][0] - pair[0][0]),
+                pair[0][1] + t * (pair[1][1] - pair[0][1]),
+            ])
+        } else {
+            None
+        }
+    })
+}
+
+/// Upper and lower yield points (ReH and ReL) for materials with a yield
+/// plateau. The upper yield point is the first maximum followed by a clear
+/// drop in stress before the curve reaches its peak, the lower yield point is
+/// the lowest stress between there and the peak.
+pub fn yield_plateau(stress_strain: &[[f64; 2]]) -> Option<([f64; 2], [f64; 2])> {
+    let (peak, peak_stress) = stress_strain
+        .iter()
+        .enumerate()
+        .map(|(i, [_, stress])| (i, *stress))
+        .max_by(|a, b| a.1.total_cmp(&b.1))?;
+
+    if peak_stress <= 0.0 {
+        return None;
+    }
+
+    let drop = peak_stress * YIELD_DROP_FRACTION;
+    let mut max = 0;
+    (0..peak).find(|&i| {
+        if stress_strain[i][1] > stress_strain[max][1] {
+            max = i;
+        }
+
+        stress_strain[max][1] - stress_strain[i][1] >= drop
+    })?;
+    let upper = max;
+
+    let lower = stress_strain[upper..peak]
+        .iter()
+        .copied()
+        .min_by(|a, b| a[1].total_cmp(&b[1]))?;
+
+    Some((stress_strain[upper], lower))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::analysis::LinearFit;
+
+    #[test]
+    fn offset_yield_intersects_the_shifted_line() {
+        // Elastic up to 200 MPa at 200 GPa, then hardening at 1 GPa.
+        let curve: Vec<[f64; 2]> = (0..=100)
+            .map(|i| {
+                let strain = i as f64 * 0.0001;
+                let stress = if strain <= 0.001 { 200_000.0 * strain } else { 200.0 + 1000.0 * (strain - 0.001) };
+                [strain, stress]
+            })
+            .collect();
+        let modulus = ModulusFit {
+            line: LinearFit { slope: 200_000.0, intercept: 0.0, r_squared: 1.0 },
+            strain_start: 0.0,
+            strain_end: 0.001,
+        };
+
+        let [strain, stress] = offset_yield(&curve, &modulus, 0.002).unwrap();
+
+        // 200000·(ε - 0.002) = 200 + 1000·(ε - 0.001)
+        let expected = 599.0 / 199_000.0;
+        assert!((strain - expected).abs() < 1e-12);
+        assert!((stress - 200_000.0 * (expected - 0.002)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn offset_yield_needs_a_crossing() {
+        let curve = [[0.0, 0.0], [0.001, 200.0], [0.002, 400.0]];
+        let modulus = ModulusFit {
+            line: LinearFit { slope: 200_000.0, intercept: 0.0, r_squared: 1.0 },
+            strain_start: 0.0,
+            strain_end: 0.002,
+        };
+
+        assert!(offset_yield(&curve, &modulus, 0.002).is_none());
+    }
+
+    #[test]
+    fn yield_plateau_finds_upper_and_lower_yield() {
+        let curve = [
+            [0.000, 0.0],
+            [0.001, 200.0],
+            [0.002, 300.0],
+            [0.003, 280.0],
+            [0.004, 270.0],
+            [0.005, 275.0],
+            [0.010, 350.0],
+            [0.020, 400.0],
+            [0.030, 380.0],
+        ];
+
+        let (upper, lower) = yield_plateau(&curve).unwrap();
+
+        assert_eq!(upper, [0.002, 300.0]);
+        assert_eq!(lower, [0.004, 270.0]);
+    }
+
+    #[test]
+    fn yield_plateau_ignores_smooth_curves() {
+        let curve: Vec<[f64; 2]> = (0..=50).map(|i| [i as f64 * 0.001, 400.0 * (1.0 - (-(i as f64) / 10.0).exp())]).collect();
+
+        assert!(yield_plateau(&curve).is_none());
+    }
+}
@@ -0,0 +1,190 @@
+//! Writing a test to a CSV file, with a block of metadata above the samples.
+
+use std::{
+    fmt::{self, Formatter},
+    io::Write,
+    path::PathBuf,
+};
+
+use anyhow::ensure;
+use strum_macros::EnumIter;
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone, Copy, PartialEq, EnumIter)]
+pub enum Delimiter {
+    Comma,
+    #[default]
+    Semicolon,
+    Tab,
+}
+
+impl fmt::Display for Delimiter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Delimiter::Comma => write!(f, "Comma"),
+            Delimiter::Semicolon => write!(f, "Semicolon"),
+            Delimiter::Tab => write!(f, "Tab"),
+        }
+    }
+}
+
+/// Why a delimiter and decimal separator can't be used together.
+pub const SEPARATOR_CLASH: &str = "Use a semicolon or tab delimiter with a decimal comma";
+
+impl Delimiter {
+    /// A comma delimiter can't be told apart from a decimal comma.
+    pub fn fits(&self, separator: DecimalSeparator) -> bool {
+        !(*self == Delimiter::Comma && separator == DecimalSeparator::Comma)
+    }
+
+    fn char(&self) -> char {
+        match self {
+            Delimiter::Comma => ',',
+            Delimiter::Semicolon => ';',
+            Delimiter::Tab => '\t',
+        }
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone, Copy, PartialEq, EnumIter)]
+pub enum DecimalSeparator {
+    #[default]
+    Point,
+    Comma,
+}
+
+impl fmt::Display for DecimalSeparator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            DecimalSeparator::Point => write!(f, "Point (1.5)"),
+            DecimalSeparator::Comma => write!(f, "Comma (1,5)"),
+        }
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+#[serde(default)]
+pub struct CsvSettings {
+    /// Folder exported files are written to.
+    pub folder: String,
+    pub delimiter: Delimiter,
+    pub decimal_separator: DecimalSeparator,
+    /// Export every test as soon as it finishes.
+    pub export_every_test: bool,
+}
+
+impl Default for CsvSettings {
+    fn default() -> Self {
+        Self {
+            folder: home_folder().to_string_lossy().into_owned(),
+            delimiter: Delimiter::default(),
+            decimal_separator: DecimalSeparator::Point,
+            export_every_test: false,
+        }
+    }
+}
+
+/// A column of the sample table.
+pub struct Column {
+    pub name: String,
+    pub decimals: usize,
+}
+
+impl CsvSettings {
+    /// Writes `metadata` as name–value rows, an empty row and then the
+    /// `columns` with one row per entry of `rows`.
+    pub fn write(
+        &self,
+        out: &mut impl Write,
+        metadata: &[(String, String)],
+        columns: &[Column],
+        rows: impl Iterator<Item = Vec<f64>>,
+    ) -> anyhow::Result<()> {
+        ensure!(self.delimiter.fits(self.decimal_separator), SEPARATOR_CLASH);
+
+        let delimiter = self.delimiter.char().to_string();
+
+        for (name, value) in metadata {
+            writeln!(out, "{}{delimiter}{}", self.text(name), self.text(value))?;
+        }
+        writeln!(out)?;
+
+        let names: Vec<String> = columns.iter().map(|column| self.text(&column.name)).collect();
+        writeln!(out, "{}", names.join(&delimiter))?;
+
+        for row in rows {
+            let values: Vec<String> = row
+                .iter()
+                .zip(columns)
+                .map(|(value, column)| self.number(*value, column.decimals))
+                .collect();
+            writeln!(out, "{}", values.join(&delimiter))?;
+        }
+
+        Ok(())
+    }
+
+    fn number(&self, value: f64, decimals: usize) -> String {
+        let text = format!("{value:.decimals$}");
+
+        match self.decimal_separator {
+            DecimalSeparator::Point => text,
+            DecimalSeparator::Comma => text.replace('.', ","),
+        }
+    }
+
+    /// Quotes text that would otherwise break the row apart.
+    fn text(&self, text: &str) -> String {
+        if text.contains([self.delimiter.char(), '"', '\n', '\r']) {
+            format!("\"{}\"", text.replace('"', "\"\""))
+        } else {
+            text.to_owned()
+        }
+    }
+}
+
+fn home_folder() -> PathBuf {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use strum::IntoEnumIterator;
+
+    use super::*;
+
+    fn export(delimiter: Delimiter, decimal_separator: DecimalSeparator) -> anyhow::Result<String> {
+        let settings = CsvSettings { delimiter, decimal_separator, ..Default::default() };
+        let metadata = [("Operator".to_owned(), "Doe; J.".to_owned()), ("Notes".to_owned(), "Said \"ok\"".to_owned())];
+        let columns = [Column { name: "Force (N)".to_owned(), decimals: 2 }, Column { name: "Time (s)".to_owned(), decimals: 1 }];
+        let mut out = Vec::new();
+        settings.write(&mut out, &metadata, &columns, [vec![1.5, 0.25], vec![-2.0, 10.0]].into_iter())?;
+        Ok(String::from_utf8(out)?)
+    }
+
+    #[test]
+    fn decimal_comma_with_semicolons() {
+        let csv = export(Delimiter::Semicolon, DecimalSeparator::Comma).unwrap();
+
+        assert_eq!(csv, "Operator;\"Doe; J.\"\nNotes;\"Said \"\"ok\"\"\"\n\nForce (N);Time (s)\n1,50;0,2\n-2,00;10,0\n");
+    }
+
+    #[test]
+    fn decimal_point_with_tabs() {
+        let csv = export(Delimiter::Tab, DecimalSeparator::Point).unwrap();
+
+        assert_eq!(csv, "Operator\tDoe; J.\nNotes\t\"Said \"\"ok\"\"\"\n\nForce (N)\tTime (s)\n1.50\t0.2\n-2.00\t10.0\n");
+    }
+
+    #[test]
+    fn decimal_comma_with_commas_is_rejected() {
+        assert!(export(Delimiter::Comma, DecimalSeparator::Comma).is_err());
+    }
+
+    #[test]
+    fn default_delimiter_fits_either_separator() {
+        assert!(DecimalSeparator::iter().all(|separator| Delimiter::default().fits(separator)));
+    }
+}
@@ -0,0 +1,263 @@
+//! Smoothing of the load cell signal. Filters only ever produce a new force
+//! series, the recorded samples are left untouched.
+
+use std::{f64::consts::PI, fmt::{self, Formatter}};
+
+use strum_macros::EnumIter;
+
+use super::sample::Sample;
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, EnumIter)]
+pub enum FilterKind {
+    None,
+    MovingAverage,
+    Median,
+    SavitzkyGolay,
+    LowPass,
+}
+
+impl fmt::Display for FilterKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterKind::None => write!(f, "None"),
+            FilterKind::MovingAverage => write!(f, "Moving average"),
+            FilterKind::Median => write!(f, "Median"),
+            FilterKind::SavitzkyGolay => write!(f, "Savitzky–Golay"),
+            FilterKind::LowPass => write!(f, "Low-pass"),
+        }
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct FilterSettings {
+    pub kind: FilterKind,
+    /// Number of samples the window filters look at, always odd.
+    pub window: usize,
+    /// Polynomial order of the Savitzky–Golay filter.
+    pub polynomial_order: usize,
+    /// Cut-off frequency of the low-pass filter in Hz.
+    pub cutoff_frequency: f64,
+}
+
+impl Default for FilterSettings {
+    fn default() -> Self {
+        Self {
+            kind: FilterKind::None,
+            window: 5,
+            polynomial_order: 2,
+            cutoff_frequency: 5.0,
+        }
+    }
+}
+
+impl FilterSettings {
+    /// Returns the filtered force of every sample.
+    pub fn apply(&self, samples: &[Sample]) -> Vec<f64> {
+        let force: Vec<f64> = samples.iter().map(|s| s.force).collect();
+        let half_window = self.window / 2;
+
+        match self.kind {
+            FilterKind::None => force,
+            FilterKind::MovingAverage => sliding_window(&force, half_window, |window| {
+                window.iter().sum::<f64>() / window.len() as f64
+            }),
+            FilterKind::Median => sliding_window(&force, half_window, |window| {
+                let mut sorted = window.to_vec();
+                sorted.sort_by(f64::total_cmp);
+                let mid = sorted.len() / 2;
+                if sorted.len() % 2 == 0 {
+                    (sorted[mid - 1] + sorted[mid]) / 2.0
+                } else {
+                    sorted[mid]
+                }
+            }),
+            FilterKind::SavitzkyGolay => savitzky_golay(&force, half_window, self.polynomial_order),
+            FilterKind::LowPass => low_pass(samples, self.cutoff_frequency),
+        }
+    }
+}
+
+/// Applies `f` to a window centered on every value, the window shrinks at
+/// the ends of the series.
+fn sliding_window(values: &[f64], half_window: usize, f: impl Fn(&[f64]) -> f64) -> Vec<f64> {
+    (0..values.len())
+        .map(|i| {
+            let start = i.saturating_sub(half_window);
+            let end = (i + half_window + 1).min(values.len());
+            f(&values[start..end])
+        })
+        .collect()
+}
+
+/// Least squares polynomial smoothing. Points near the ends are evaluated on
+/// the first or last full window instead of a truncated one.
+fn savitzky_golay(values: &[f64], half_window: usize, order: usize) -> Vec<f64> {
+    let window = 2 * half_window + 1;
+    if half_window == 0 || values.len() < window || order + 1 >= window {
+        return values.to_vec();
+    }
+
+    let weights_at = |offset: i32| savitzky_golay_weights(half_window, order, offset);
+    let apply = |weights: &[f64], start: usize| {
+        weights.iter().zip(&values[start..start + window]).map(|(w, v)| w * v).sum::<f64>()
+    };
+
+    let center = weights_at(0);
+    let last_start = values.len() - window;
+
+    (0..values.len())
+        .map(|i| {
+            if i < half_window {
+                apply(&weights_at(i as i32 - half_window as i32), 0)
+            } else if i > last_start + half_window {
+                apply(&weights_at((i - last_start) as i32 - half_window as i32), last_start)
+            } else {
+                apply(&center, i - half_window)
+            }
+        })
+        .collect()
+}
+
+/// Weights that evaluate a polynomial fit of `order` over the window
+/// `-half_window..=half_window` at `offset`.
+fn savitzky_golay_weights(half_window: usize, order: usize, offset: i32) -> Vec<f64> {
+    let m = half_window as i32;
+    let terms = order + 1;
+
+    // Normal equations (JᵀJ) x = v, with J[i][k] = iᵏ and v[k] = offsetᵏ.
+    let mut normal = vec![vec![0.0; terms + 1]; terms];
+    for (row, equation) in normal.iter_mut().enumerate() {
+        for (col, value) in equation.iter_mut().take(terms).enumerate() {
+            *value = (-m..=m).map(|i| (i as f64).powi((row + col) as i32)).sum();
+        }
+        equation[terms] = (offset as f64).powi(row as i32);
+    }
+
+    let x = solve(normal);
+
+    (-m..=m)
+        .map(|i| x.iter().enumerate().map(|(k, xk)| (i as f64).powi(k as i32) * xk).sum())
+        .collect()
+}
+
+/// Gauss–Jordan elimination with partial pivoting on an augmented matrix.
+fn solve(mut matrix: Vec<Vec<f64>>) -> Vec<f64> {
+    let n = matrix.len();
+
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|&a, &b| matrix[a][col].abs().total_cmp(&matrix[b][col].abs()))
+            .unwrap_or(col);
+        matrix.swap(col, pivot);
+
+        let divisor = matrix[col][col];
+        matrix[col].iter_mut().for_each(|v| *v /= divisor);
+
+        for row in 0..n {
+            if row != col {
+                let factor = matrix[row][col];
+                let pivot_row = matrix[col].clone();
+                matrix[row].iter_mut().zip(pivot_row).for_each(|(v, p)| *v -= factor * p);
+            }
+        }
+    }
+
+    matrix.iter().map(|row| row[n]).collect()
+}
+
+/// First order low-pass filter run forwards and backwards so the filtered
+/// signal does not lag behind the measurement.
+fn low_pass(samples: &[Sample], cutoff_frequency: f64) -> Vec<f64> {
+    let mut force: Vec<f64> = samples.iter().map(|s| s.force).collect();
+    if force.is_empty() || cutoff_frequency <= 0.0 {
+        return force;
+    }
+
+    let rc = 1.0 / (2.0 * PI * cutoff_frequency);
+    let alpha = |i: usize, j: usize| {
+        let dt = (samples[i].time - samples[j].time).abs();
+        dt / (rc + dt)
+    };
+
+    for i in 1..force.len() {
+        force[i] = force[i - 1] + alpha(i, i - 1) * (force[i] - force[i - 1]);
+    }
+
+    for i in (0..force.len() - 1).rev() {
+        force[i] = force[i + 1] + alpha(i, i + 1) * (force[i] - force[i + 1]);
+    }
+
+    force
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples(force: &[f64]) -> Vec<Sample> {
+        force.iter().enumerate().map(|(i, force)| Sample { time: i as f64 * 0.01, force: *force, ..Default::default() }).collect()
+    }
+
+    fn settings(kind: FilterKind, window: usize) -> FilterSettings {
+        FilterSettings { kind, window, ..Default::default() }
+    }
+
+    fn assert_close(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-9, "{actual:?} != {expected:?}");
+        }
+    }
+
+    #[test]
+    fn no_filter_keeps_the_force() {
+        let force = [1.0, 5.0, 2.0];
+
+        assert_eq!(settings(FilterKind::None, 3).apply(&samples(&force)), force);
+    }
+
+    #[test]
+    fn moving_average_shrinks_the_window_at_the_ends() {
+        let filtered = settings(FilterKind::MovingAverage, 3).apply(&samples(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]));
+
+        assert_close(&filtered, &[1.5, 2.0, 3.0, 4.0, 5.0, 5.5]);
+    }
+
+    #[test]
+    fn median_removes_spikes_up_to_the_ends() {
+        let filtered = settings(FilterKind::Median, 3).apply(&samples(&[9.0, 1.0, 1.0, 10.0, 1.0, 1.0]));
+
+        assert_close(&filtered, &[5.0, 1.0, 1.0, 1.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn savitzky_golay_keeps_polynomials_up_to_the_ends() {
+        let force: Vec<f64> = (0..12).map(|i| (i * i) as f64 - 3.0 * i as f64 + 2.0).collect();
+        let filter = FilterSettings { polynomial_order: 2, ..settings(FilterKind::SavitzkyGolay, 5) };
+
+        assert_close(&filter.apply(&samples(&force)), &force);
+    }
+
+    #[test]
+    fn savitzky_golay_leaves_short_series_alone() {
+        let force = [1.0, 4.0, 2.0];
+
+        assert_eq!(settings(FilterKind::SavitzkyGolay, 5).apply(&samples(&force)), force);
+    }
+
+    #[test]
+    fn low_pass_does_not_lag() {
+        let force: Vec<f64> = (0..200).map(|i| if i < 100 { 0.0 } else { 10.0 }).collect();
+        let filtered = FilterSettings { cutoff_frequency: 5.0, ..settings(FilterKind::LowPass, 5) }.apply(&samples(&force));
+
+        assert!(filtered[0].abs() < 1e-3 && (filtered[199] - 10.0).abs() < 1e-3);
+        // Running both ways spreads the step evenly around where it happens.
+        assert!((filtered[99] + filtered[100] - 10.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn low_pass_handles_empty_series() {
+        assert!(settings(FilterKind::LowPass, 5).apply(&[]).is_empty());
+    }
+}
@@ -0,0 +1,347 @@
+//! Every completed test, stored automatically in a local folder: one test
+//! file per test and an index of them that the history browser searches.
+
+use std::{fs, path::{Path, PathBuf}};
+
+use anyhow::Context;
+use chrono::{DateTime, Local, NaiveDate};
+use log::warn;
+
+use super::test_file::{self, TestFile};
+use super::test_program::{TestMode, TestProcedure};
+
+const INDEX_FILE: &str = "index.json";
+
+/// What the history browser shows and searches of a stored test.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct HistoryEntry {
+    pub id: u64,
+    pub date: Option<DateTime<Local>>,
+    pub machine: String,
+    pub mode: TestMode,
+    pub procedure: TestProcedure,
+    pub operator: String,
+    pub customer: String,
+    pub material: String,
+    pub batch: String,
+    pub specimen_id: String,
+    /// Archived entries are kept but hidden unless asked for.
+    pub archived: bool,
+}
+
+impl HistoryEntry {
+    fn new(id: u64, test: &TestFile) -> Self {
+        Self {
+            id,
+            date: test.metadata.date,
+            machine: test.machine_profile.name.clone(),
+            mode: test.metadata.mode,
+            procedure: test.metadata.procedure,
+            operator: test.metadata.info.operator.clone(),
+            customer: test.metadata.info.customer.clone(),
+            material: test.metadata.info.material.clone(),
+            batch: test.metadata.info.batch.clone(),
+            specimen_id: test.metadata.info.specimen_id.clone(),
+            archived: false,
+        }
+    }
+
+    fn file_name(&self) -> String {
+        file_name(self.id)
+    }
+
+    /// Text the search field is matched against.
+    fn search_text(&self) -> String {
+        [
+            &self.machine,
+            &self.mode.to_string(),
+            &self.procedure.to_string(),
+            &self.operator,
+            &self.customer,
+            &self.material,
+            &self.batch,
+            &self.specimen_id,
+        ]
+        .map(|text| text.as_str())
+        .join(" ")
+        .to_lowercase()
+    }
+}
+
+/// The stored tests, oldest first.
+#[derive(Default)]
+pub struct History {
+    folder: PathBuf,
+    entries: Vec<HistoryEntry>,
+}
+
+impl History {
+    /// Reads the index in `folder`, an empty history when there is none yet.
+    /// Entries whose file is gone are dropped and test files the index
+    /// misses are added, a damaged index is rebuilt from the files.
+    pub fn open(folder: PathBuf) -> anyhow::Result<Self> {
+        let entries = match fs::read_to_string(folder.join(INDEX_FILE)) {
+            Ok(text) => serde_json::from_str(&text).unwrap_or_else(|err| {
+                warn!("Rebuilding the damaged test history index: {err}");
+                Vec::new()
+            }),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut history = Self { folder, entries };
+        if history.check()? {
+            history.write_index()?;
+        }
+        Ok(history)
+    }
+
+    /// Brings the index in line with the test files, returns whether it changed.
+    fn check(&mut self) -> anyhow::Result<bool> {
+        let ids: Vec<u64> = match fs::read_dir(&self.folder) {
+            Ok(files) => files
+                .filter_map(Result::ok)
+                .filter_map(|file| {
+                    let path = file.path();
+                    let is_test = path.extension().is_some_and(|extension| extension == test_file::EXTENSION);
+                    path.file_stem()?.to_str()?.parse::<u64>().ok().filter(|_| is_test)
+                })
+                .collect(),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(err) => return Err(err.into()),
+        };
+
+        let count = self.entries.len();
+        self.entries.retain(|entry| ids.contains(&entry.id));
+        let mut changed = self.entries.len() != count;
+        if changed {
+            warn!("Dropped {} test history entries without a test file", count - self.entries.len());
+        }
+
+        for id in ids {
+            if self.entries.iter().any(|entry| entry.id == id) {
+                continue;
+            }
+            let path = self.folder.join(file_name(id));
+            match TestFile::open(&path) {
+                Ok(test) => {
+                    self.entries.push(HistoryEntry::new(id, &test));
+                    changed = true;
+                },
+                Err(err) => warn!("Leaving {} out of the test history: {err}", path.display()),
+            }
+        }
+
+        self.entries.sort_by_key(|entry| entry.id);
+        Ok(changed)
+    }
+
+    /// Folder of the history in the app's data folder.
+    pub fn default_folder() -> Option<PathBuf> {
+        eframe::storage_dir(crate::APP_ID).map(|folder| folder.join("history"))
+    }
+
+    pub fn folder(&self) -> &Path {
+        &self.folder
+    }
+
+    pub fn entries(&self) -> &[HistoryEntry] {
+        &self.entries
+    }
+
+    /// Stores the test, returns the id it is stored under.
+    pub fn add(&mut self, test: &TestFile) -> anyhow::Result<u64> {
+        fs::create_dir_all(&self.folder)?;
+
+        let mut id = self.entries.iter().map(|entry| entry.id + 1).max().unwrap_or(1);
+        // Never overwrite a file the index left out because it couldn't be read.
+        while self.folder.join(file_name(id)).exists() {
+            id += 1;
+        }
+        let entry = HistoryEntry::new(id, test);
+        test.save(&self.folder.join(entry.file_name()))?;
+
+        self.entries.push(entry);
+        self.write_index()?;
+        Ok(id)
+    }
+
+    pub fn contains(&self, id: u64) -> bool {
+        self.entries.iter().any(|entry| entry.id == id)
+    }
+
+    pub fn load(&self, id: u64) -> anyhow::Result<TestFile> {
+        TestFile::open(&self.path(id)?)
+    }
+
+    pub fn set_archived(&mut self, id: u64, archived: bool) -> anyhow::Result<()> {
+        if let Some(entry) = self.entries.iter_mut().find(|entry| entry.id == id) {
+            entry.archived = archived;
+        }
+        self.write_index()
+    }
+
+    /// Removes the test from the index and deletes its file.
+    pub fn delete(&mut self, id: u64) -> anyhow::Result<()> {
+        let path = self.path(id)?;
+        self.entries.retain(|entry| entry.id != id);
+        self.write_index()?;
+
+        match fs::remove_file(path) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn path(&self, id: u64) -> anyhow::Result<PathBuf> {
+        let entry = self.entries.iter().find(|entry| entry.id == id).context("No such test in the history")?;
+        Ok(self.folder.join(entry.file_name()))
+    }
+
+    /// Writes the index next to the old one first, so a crash never leaves
+    /// half an index behind.
+    fn write_index(&self) -> anyhow::Result<()> {
+        let path = self.folder.join(INDEX_FILE);
+        let temporary = path.with_extension("json.tmp");
+
+        fs::write(&temporary, serde_json::to_vec(&self.entries)?)?;
+        fs::rename(temporary, path)?;
+        Ok(())
+    }
+}
+
+fn file_name(id: u64) -> String {
+    format!("{id}.{}", test_file::EXTENSION)
+}
+
+/// The search fields of the history browser.
+#[derive(Default)]
+pub struct HistoryFilter {
+    /// First day shown, as `YYYY-MM-DD`, empty for no limit.
+    pub from: String,
+    /// Last day shown, as `YYYY-MM-DD`, empty for no limit.
+    pub to: String,
+    pub material: String,
+    pub operator: String,
+    pub specimen_id: String,
+    /// Searched for in every field.
+    pub text: String,
+    pub show_archived: bool,
+}
+
+impl HistoryFilter {
+    pub fn matches(&self, entry: &HistoryEntry) -> bool {
+        let day = entry.date.map(|date| date.date_naive());
+        let after = |limit: &str| parse_day(limit).map_or(true, |limit| day.is_some_and(|day| day >= limit));
+        let before = |limit: &str| parse_day(limit).map_or(true, |limit| day.is_some_and(|day| day <= limit));
+
+        (self.show_archived || !entry.archived)
+            && after(&self.from)
+            && before(&self.to)
+            && contains(&entry.material, &self.material)
+            && contains(&entry.operator, &self.operator)
+            && contains(&entry.specimen_id, &self.specimen_id)
+            && contains(&entry.search_text(), &self.text)
+    }
+}
+
+/// Case insensitive search, an empty search matches anything.
+fn contains(text: &str, search: &str) -> bool {
+    text.to_lowercase().contains(&search.trim().to_lowercase())
+}
+
+/// A day typed as `YYYY-MM-DD`, `None` for anything else.
+fn parse_day(text: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(text.trim(), "%Y-%m-%d").ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn entry(year: i32, month: u32, day: u32, hour: u32) -> HistoryEntry {
+        HistoryEntry {
+            date: Local.with_ymd_and_hms(year, month, day, hour, 30, 0).single(),
+            material: "S235JR".to_owned(),
+            operator: "Anna".to_owned(),
+            ..Default::default()
+        }
+    }
+
+    fn between(from: &str, to: &str) -> HistoryFilter {
+        HistoryFilter { from: from.to_owned(), to: to.to_owned(), ..Default::default() }
+    }
+
+    #[test]
+    fn date_bounds_include_the_whole_day() {
+        let filter = between("2024-03-01", "2024-03-31");
+
+        assert!(filter.matches(&entry(2024, 3, 1, 0)));
+        assert!(filter.matches(&entry(2024, 3, 31, 23)));
+        assert!(!filter.matches(&entry(2024, 2, 29, 23)));
+        assert!(!filter.matches(&entry(2024, 4, 1, 0)));
+    }
+
+    #[test]
+    fn open_date_bounds() {
+        assert!(between("", "2024-03-31").matches(&entry(1999, 1, 1, 12)));
+        assert!(!between("", "2024-03-31").matches(&entry(2024, 4, 1, 12)));
+        assert!(between("2024-03-01", "").matches(&entry(2099, 1, 1, 12)));
+        assert!(!between("2024-03-01", "").matches(&entry(2024, 2, 1, 12)));
+    }
+
+    #[test]
+    fn unfinished_dates_are_ignored() {
+        assert!(between("2024-0", " 2024-13-01 ").matches(&entry(2024, 4, 1, 12)));
+        assert!(between(" 2024-03-01 ", "").matches(&entry(2024, 3, 1, 12)));
+    }
+
+    #[test]
+    fn date_bounds_leave_out_undated_tests() {
+        let undated = HistoryEntry::default();
+
+        assert!(between("", "").matches(&undated));
+        assert!(!between("2024-03-01", "").matches(&undated));
+        assert!(!between("", "2024-03-01").matches(&undated));
+    }
+
+    #[test]
+    fn fields_and_archive() {
+        let mut entry = entry(2024, 3, 1, 12);
+        let filter = HistoryFilter { material: " s235 ".to_owned(), text: "anna".to_owned(), ..Default::default() };
+        assert!(filter.matches(&entry));
+
+        entry.archived = true;
+        assert!(!filter.matches(&entry));
+        assert!(HistoryFilter { show_archived: true, ..filter }.matches(&entry));
+    }
+
+    #[test]
+    fn open_brings_the_index_in_line_with_the_files() {
+        let folder = std::env::temp_dir().join(format!("tensile_history_{}", std::process::id()));
+        let mut history = History::open(folder.clone()).unwrap();
+        let mut test = TestFile::default();
+        test.metadata.info.specimen_id = "A1".to_owned();
+        let first = history.add(&test).unwrap();
+        let second = history.add(&test).unwrap();
+
+        fs::remove_file(folder.join(file_name(first))).unwrap();
+        test.save(&folder.join(file_name(7))).unwrap();
+        fs::write(folder.join(file_name(8)), "not a test").unwrap();
+
+        let mut history = History::open(folder.clone()).unwrap();
+        let ids: Vec<u64> = history.entries().iter().map(|entry| entry.id).collect();
+        assert_eq!(ids, vec![second, 7]);
+        assert_eq!(history.entries()[1].specimen_id, "A1");
+        assert_eq!(history.add(&test).unwrap(), 9, "the unreadable file is kept");
+
+        fs::write(folder.join(INDEX_FILE), "{").unwrap();
+        let history = History::open(folder.clone()).unwrap();
+        assert_eq!(history.entries().len(), 3);
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+}
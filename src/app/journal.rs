@@ -0,0 +1,116 @@
+//! Journal of the running test on disk, so a crash doesn't lose it. The
+//! first line holds the test without samples, every following line one
+//! sample. The journal is removed once the test finishes normally, one that
+//! is still there at startup belongs to an interrupted test.
+
+use std::{
+    fs,
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+
+use super::sample::Sample;
+use super::test_file::TestFile;
+
+pub struct Journal {
+    path: PathBuf,
+    out: BufWriter<fs::File>,
+}
+
+impl Journal {
+    /// Where the journal lives, in the app's data folder.
+    pub fn default_path() -> Option<PathBuf> {
+        eframe::storage_dir(crate::APP_ID).map(|folder| folder.join("journal.jsonl"))
+    }
+
+    /// Starts a journal for `test`, replacing any previous one.
+    pub fn create(path: PathBuf, test: &TestFile) -> anyhow::Result<Self> {
+        if let Some(folder) = path.parent() {
+            fs::create_dir_all(folder)?;
+        }
+
+        let mut out = BufWriter::new(fs::File::create(&path)?);
+        serde_json::to_writer(&mut out, test)?;
+        writeln!(out)?;
+        out.flush()?;
+
+        Ok(Self { path, out })
+    }
+
+    pub fn append(&mut self, sample: &Sample) -> anyhow::Result<()> {
+        serde_json::to_writer(&mut self.out, sample)?;
+        writeln!(self.out)?;
+        Ok(())
+    }
+
+    /// Writes the appended samples to disk.
+    pub fn flush(&mut self) -> anyhow::Result<()> {
+        self.out.flush()?;
+        Ok(())
+    }
+
+    /// Removes the journal of a test that finished normally.
+    pub fn finish(self) -> anyhow::Result<()> {
+        drop(self.out);
+        fs::remove_file(self.path)?;
+        Ok(())
+    }
+}
+
+/// Reads the test of a journal left behind. A sample line cut short by the
+/// crash ends the test there.
+pub fn recover(path: &Path) -> anyhow::Result<TestFile> {
+    let mut lines = BufReader::new(fs::File::open(path)?).lines();
+
+    let header = lines.next().context("The journal is empty")??;
+    let mut test: TestFile = serde_json::from_str(&header).context("The journal is damaged")?;
+
+    for line in lines {
+        match serde_json::from_str(&line?) {
+            Ok(sample) => test.samples.push(sample),
+            Err(_) => break,
+        }
+    }
+
+    Ok(test)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recovers_the_samples_before_a_cut_off_line() {
+        let path = std::env::temp_dir().join(format!("tensile_journal_{}", std::process::id())).join("journal.jsonl");
+        let mut test = TestFile::default();
+        test.metadata.preload_force = Some(12.5);
+
+        let mut journal = Journal::create(path.clone(), &test).unwrap();
+        for i in 0..3 {
+            journal.append(&Sample { time: i as f64, force: 10.0 * i as f64, ..Default::default() }).unwrap();
+        }
+        journal.flush().unwrap();
+        // The crash cuts the last sample short.
+        write!(journal.out, "{{\"time\":3.0,\"for").unwrap();
+        journal.flush().unwrap();
+
+        let recovered = recover(&path).unwrap();
+        assert_eq!(recovered.metadata.preload_force, Some(12.5));
+        assert_eq!(recovered.samples.iter().map(|s| s.force).collect::<Vec<_>>(), [0.0, 10.0, 20.0]);
+
+        journal.finish().unwrap();
+        assert!(!path.exists());
+        assert!(recover(&path).is_err());
+    }
+
+    #[test]
+    fn empty_journal_is_not_a_test() {
+        let path = std::env::temp_dir().join(format!("tensile_empty_journal_{}.jsonl", std::process::id()));
+        fs::write(&path, "").unwrap();
+
+        assert!(recover(&path).is_err());
+        fs::remove_file(path).unwrap();
+    }
+}
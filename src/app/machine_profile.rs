@@ -0,0 +1,131 @@
+/// Properties of the machine itself, shared by every test run on it.
+#[derive(serde::Deserialize, serde::Serialize, Clone)]
+#[serde(default)]
+pub struct MachineProfile {
+    pub name: String,
+    pub compliance: Option<Compliance>,
+    /// Whether `compliance` is subtracted from the measured extension.
+    pub correct_compliance: bool,
+}
+
+impl Default for MachineProfile {
+    fn default() -> Self {
+        Self {
+            name: "Industrio tensile tester".to_owned(),
+            compliance: None,
+            correct_compliance: true,
+        }
+    }
+}
+
+/// Deflection of the frame and grips as a function of force, measured by
+/// pulling on a rigid specimen: `deflection = linear·F + quadratic·F²`.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy)]
+pub struct Compliance {
+    /// mm/N
+    pub linear: f64,
+    /// mm/N²
+    pub quadratic: f64,
+    /// Highest force reached during calibration in N.
+    pub max_force: f64,
+}
+
+impl Compliance {
+    /// Fits the compliance through the origin to the `[extension, force]`
+    /// curve of a calibration run, up to its maximum force.
+    pub fn calibrate(curve: &[[f64; 2]]) -> Option<Self> {
+        let (peak, max_force) = curve
+            .iter()
+            .enumerate()
+            .map(|(i, [_, force])| (i, *force))
+            .max_by(|a, b| a.1.total_cmp(&b.1))?;
+
+        if max_force <= 0.0 {
+            return None;
+        }
+
+        // Least squares normal equations for d = a·F + b·F².
+        let (mut f2, mut f3, mut f4, mut df, mut df2) = (0.0, 0.0, 0.0, 0.0, 0.0);
+        for [deflection, force] in curve[..=peak].iter().filter(|[_, force]| *force > 0.0) {
+            f2 += force.powi(2);
+            f3 += force.powi(3);
+            f4 += force.powi(4);
+            df += deflection * force;
+            df2 += deflection * force.powi(2);
+        }
+
+        let determinant = f2 * f4 - f3 * f3;
+        if determinant.abs() <= f64::EPSILON {
+            return None;
+        }
+
+        Some(Self {
+            linear: (df * f4 - df2 * f3) / determinant,
+            quadratic: (f2 * df2 - f3 * df) / determinant,
+            max_force,
+        })
+    }
+
+    pub fn deflection(&self, force: f64) -> f64 {
+        self.linear * force + self.quadratic * force * force
+    }
+
+    /// Removes the frame and grip deflection from an `[extension, force]` curve.
+    pub fn correct(&self, curve: &mut [[f64; 2]]) {
+        curve
+            .iter_mut()
+            .for_each(|[extension, force]| *extension -= self.deflection(*force));
+    }
+
+    /// Frame stiffness at `force` in N/mm.
+    pub fn stiffness(&self, force: f64) -> f64 {
+        1.0 / (self.linear + 2.0 * self.quadratic * force)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A frame of 5000 N/mm loaded to 1000 N and unloaded again.
+    fn frame_curve() -> Vec<[f64; 2]> {
+        (0..=20).chain((0..20).rev()).map(|i| [i as f64 * 50.0 / 5000.0, i as f64 * 50.0]).collect()
+    }
+
+    #[test]
+    fn calibrates_a_linear_frame() {
+        let compliance = Compliance::calibrate(&frame_curve()).unwrap();
+
+        assert!((compliance.stiffness(0.0) - 5000.0).abs() < 1e-6);
+        assert!((compliance.stiffness(1000.0) - 5000.0).abs() < 1e-6);
+        assert!(compliance.quadratic.abs() < 1e-15);
+        assert_eq!(compliance.max_force, 1000.0);
+    }
+
+    #[test]
+    fn fits_the_quadratic_term() {
+        let curve: Vec<[f64; 2]> = (0..=20).map(|i| i as f64 * 50.0).map(|force| [2e-4 * force + 1e-7 * force * force, force]).collect();
+        let compliance = Compliance::calibrate(&curve).unwrap();
+
+        assert!((compliance.linear - 2e-4).abs() < 1e-12);
+        assert!((compliance.quadratic - 1e-7).abs() < 1e-15);
+    }
+
+    #[test]
+    fn correction_leaves_the_specimen_extension() {
+        let compliance = Compliance::calibrate(&frame_curve()).unwrap();
+        // A 1000 N/mm specimen in the 5000 N/mm frame.
+        let mut curve = [[0.0, 0.0], [0.6, 500.0], [1.2, 1000.0]];
+        compliance.correct(&mut curve);
+
+        for ([extension, force], expected) in curve.iter().zip([0.0, 0.5, 1.0]) {
+            assert!((extension - expected).abs() < 1e-9, "{extension} at {force} N");
+        }
+    }
+
+    #[test]
+    fn no_calibration_without_force() {
+        assert!(Compliance::calibrate(&[]).is_none());
+        assert!(Compliance::calibrate(&[[0.0, 0.0], [0.1, 0.0]]).is_none());
+    }
+}
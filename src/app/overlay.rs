@@ -0,0 +1,171 @@
+//! Curves of other tests drawn over the current one for comparison.
+
+use std::fmt::{self, Formatter};
+
+use egui::Color32;
+use strum_macros::EnumIter;
+
+use super::analysis::{Analysis, AnalysisSettings, Specimen};
+use super::filter::FilterSettings;
+use super::sample::Sample;
+use super::test_file::TestFile;
+
+/// Colours given to overlays in turn, picked to stand out from the current
+/// curve and from each other on the dark background.
+pub const COLORS: [Color32; 8] = [
+    Color32::from_rgb(86, 180, 233),
+    Color32::from_rgb(230, 159, 0),
+    Color32::from_rgb(0, 158, 115),
+    Color32::from_rgb(240, 228, 66),
+    Color32::from_rgb(204, 121, 167),
+    Color32::from_rgb(213, 94, 0),
+    Color32::from_rgb(148, 103, 189),
+    Color32::from_rgb(140, 140, 140),
+];
+
+/// The point curves are lined up at.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone, Copy, PartialEq, EnumIter)]
+pub enum Alignment {
+    /// Curves are drawn as recorded.
+    #[default]
+    None,
+    /// Where the curve starts to carry load, which takes out slack in the grips.
+    ZeroPoint,
+    Yield,
+}
+
+impl fmt::Display for Alignment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Alignment::None => write!(f, "None"),
+            Alignment::ZeroPoint => write!(f, "Zero point"),
+            Alignment::Yield => write!(f, "Yield"),
+        }
+    }
+}
+
+impl Alignment {
+    /// Extension in mm of the point a curve is aligned at, `None` when
+    /// curves aren't aligned or the curve doesn't have the point.
+    pub fn point(&self, curve: &[[f64; 2]], specimen: &Specimen, analysis: &Analysis) -> Option<f64> {
+        match self {
+            Alignment::None => None,
+            Alignment::ZeroPoint => zero_point(curve, specimen, analysis),
+            Alignment::Yield => {
+                let point = analysis.offset_yield.or(analysis.upper_yield)?;
+                Some(specimen.force_extension(point)[0])
+            },
+        }
+    }
+}
+
+/// Where the modulus fit crosses zero stress, or without one where the force
+/// first exceeds 1% of its maximum.
+fn zero_point(curve: &[[f64; 2]], specimen: &Specimen, analysis: &Analysis) -> Option<f64> {
+    if let Some(fit) = analysis.modulus.filter(|_| specimen.is_valid()) {
+        return Some(fit.line.x(0.0) * specimen.gauge_length);
+    }
+
+    let max_force = analysis.results?.max_force;
+    curve.iter().find(|[_, force]| *force > 0.01 * max_force).map(|[extension, _]| *extension)
+}
+
+/// A test drawn over the current one.
+pub struct Overlay {
+    pub name: String,
+    pub color: Color32,
+    pub visible: bool,
+    /// The test in the history, if it came from there.
+    pub history_id: Option<u64>,
+    pub samples: Vec<Sample>,
+    /// Filtered and compliance corrected `[extension, force]` curve.
+    pub curve: Vec<[f64; 2]>,
+    pub specimen: Specimen,
+    pub analysis: Analysis,
+}
+
+impl Overlay {
+    /// Filters and analyses `test` like the current test, so the curves
+    /// compare like for like.
+    pub fn new(test: TestFile, color: Color32, filter: &FilterSettings, settings: &AnalysisSettings) -> Self {
+        let metadata = &test.metadata;
+        let name = match (&metadata.info.specimen_id, metadata.date) {
+            (id, _) if !id.is_empty() => id.clone(),
+            (_, Some(date)) => date.format("%Y-%m-%d %H:%M").to_string(),
+            (_, None) => "Test".to_owned(),
+        };
+
+        let force = filter.apply(&test.samples);
+        let mut curve: Vec<[f64; 2]> = test.samples.iter().zip(force).map(|(s, force)| [s.extension, force]).collect();
+        if let Some(compliance) = metadata.compliance {
+            compliance.correct(&mut curve);
+        }
+        let specimen = metadata.parameters.specimen();
+        let analysis = Analysis::new(&curve, &specimen, settings);
+
+        Self {
+            name,
+            color,
+            visible: true,
+            history_id: None,
+            samples: test.samples,
+            curve,
+            specimen,
+            analysis,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::analysis::{LinearFit, ModulusFit, TensileResults};
+
+    /// 1 mm of slack, then 2000 N/mm up to 1000 N.
+    fn slack_curve() -> Vec<[f64; 2]> {
+        (0..=150).map(|i| i as f64 * 0.01).map(|extension| [extension, 2000.0 * (extension - 1.0).max(0.0)]).collect()
+    }
+
+    fn analysis(curve: &[[f64; 2]], modulus: Option<ModulusFit>) -> Analysis {
+        Analysis { results: TensileResults::new(curve), modulus, ..Default::default() }
+    }
+
+    #[test]
+    fn zero_point_of_the_modulus_fit() {
+        let curve = slack_curve();
+        let specimen = Specimen { area: 10.0, gauge_length: 50.0 };
+        // 10 GPa crossing zero stress at 2% strain.
+        let line = LinearFit { slope: 10_000.0, intercept: -200.0, r_squared: 1.0 };
+        let fit = ModulusFit { line, strain_start: 0.021, strain_end: 0.029 };
+
+        let point = Alignment::ZeroPoint.point(&curve, &specimen, &analysis(&curve, Some(fit))).unwrap();
+        assert!((point - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn zero_point_without_a_modulus_fit() {
+        let curve = slack_curve();
+        let specimen = Specimen { area: 10.0, gauge_length: 50.0 };
+
+        // The first sample above 10 N, 1% of the maximum force.
+        let point = Alignment::ZeroPoint.point(&curve, &specimen, &analysis(&curve, None)).unwrap();
+        assert!((point - 1.01).abs() < 1e-9);
+
+        // A fit can't be used without the geometry it was made with.
+        let line = LinearFit { slope: 10_000.0, intercept: -100.0, r_squared: 1.0 };
+        let fit = ModulusFit { line, strain_start: 0.011, strain_end: 0.019 };
+        let no_geometry = Specimen { area: 0.0, gauge_length: 0.0 };
+        let point = Alignment::ZeroPoint.point(&curve, &no_geometry, &analysis(&curve, Some(fit))).unwrap();
+        assert!((point - 1.01).abs() < 1e-9);
+    }
+
+    #[test]
+    fn no_alignment_point() {
+        let curve = slack_curve();
+        let specimen = Specimen { area: 10.0, gauge_length: 50.0 };
+
+        assert_eq!(Alignment::None.point(&curve, &specimen, &analysis(&curve, None)), None);
+        assert_eq!(Alignment::ZeroPoint.point(&[], &specimen, &Analysis::default()), None);
+        assert_eq!(Alignment::Yield.point(&curve, &specimen, &analysis(&curve, None)), None);
+    }
+}
@@ -0,0 +1,337 @@
+//! The plot as an image for documents: curves, markers, axes and legend
+//! drawn off-screen into an SVG document or a PNG, without the GPU.
+
+mod raster;
+mod svg;
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Formatter};
+
+use ab_glyph::{Font as _, FontArc, PxScale, ScaleFont as _};
+use anyhow::Context as _;
+use egui::Color32;
+use egui_plot::{Line, LineStyle, MarkerShape, PlotBounds, Points};
+use strum_macros::EnumIter;
+
+use self::raster::Raster;
+use self::svg::Svg;
+use super::report::{tick_step, ticks};
+
+/// Width in pixels the layout is designed at, sizes scale with the image.
+const DESIGN_WIDTH: f32 = 1000.0;
+const LINE_WIDTH: f32 = 1.5;
+const MARKER_RADIUS: f32 = 4.0;
+const TICK_SIZE: f32 = 12.0;
+const LABEL_SIZE: f32 = 14.0;
+
+/// Largest side of an image in pixels, keeps a typo from eating the memory.
+pub const MAX_SIZE: u32 = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, EnumIter)]
+pub enum ImageFormat {
+    Png,
+    Svg,
+}
+
+impl fmt::Display for ImageFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ImageFormat::Png => write!(f, "PNG"),
+            ImageFormat::Svg => write!(f, "SVG"),
+        }
+    }
+}
+
+impl ImageFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Svg => "svg",
+        }
+    }
+}
+
+/// A line of the plot, drawn on screen and into images alike.
+pub struct PlotLine {
+    pub name: String,
+    pub color: Color32,
+    pub style: LineStyle,
+    pub points: Vec<[f64; 2]>,
+}
+
+impl PlotLine {
+    pub fn new(name: impl Into<String>, color: Color32, points: Vec<[f64; 2]>) -> Self {
+        Self { name: name.into(), color, style: LineStyle::Solid, points }
+    }
+
+    pub fn style(mut self, style: LineStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn line(&self) -> Line {
+        Line::new(self.points.clone()).name(&self.name).color(self.color).style(self.style)
+    }
+}
+
+/// A labelled point of the plot, drawn on screen and into images alike.
+pub struct PlotMarker {
+    pub name: String,
+    pub color: Color32,
+    pub shape: MarkerShape,
+    pub point: [f64; 2],
+}
+
+impl PlotMarker {
+    pub fn new(name: impl Into<String>, color: Color32, shape: MarkerShape, point: [f64; 2]) -> Self {
+        Self { name: name.into(), color, shape, point }
+    }
+
+    pub fn points(&self) -> Points {
+        Points::new(vec![self.point]).name(&self.name).shape(self.shape).radius(MARKER_RADIUS).color(self.color)
+    }
+}
+
+/// The plot as it is shown, lines are drawn in order under the markers.
+pub struct PlotImage {
+    pub x_label: String,
+    pub y_label: String,
+    /// The part of the plot in view.
+    pub bounds: PlotBounds,
+    pub lines: Vec<PlotLine>,
+    pub markers: Vec<PlotMarker>,
+    pub background: Color32,
+    pub text_color: Color32,
+}
+
+impl PlotImage {
+    /// The image file, `width` by `height` pixels.
+    pub fn encode(&self, format: ImageFormat, width: u32, height: u32) -> anyhow::Result<Vec<u8>> {
+        let font = font()?;
+        match format {
+            ImageFormat::Png => {
+                let mut raster = Raster::new(width, height, font)?;
+                self.draw(&mut raster, width as f32, height as f32);
+                raster.finish()
+            },
+            ImageFormat::Svg => {
+                let mut svg = Svg::new(width, height, font);
+                self.draw(&mut svg, width as f32, height as f32);
+                Ok(svg.finish().into_bytes())
+            },
+        }
+    }
+
+    fn draw(&self, canvas: &mut impl Canvas, width: f32, height: f32) {
+        let scale = width / DESIGN_WIDTH;
+        let area = Rect {
+            left: 80.0 * scale,
+            top: 40.0 * scale,
+            right: width - 20.0 * scale,
+            bottom: height - 60.0 * scale,
+        };
+        let [x_min, y_min] = self.bounds.min();
+        let [x_max, y_max] = self.bounds.max();
+        let to_image = |[x, y]: [f64; 2]| {
+            [
+                area.left + ((x - x_min) / (x_max - x_min)) as f32 * area.width(),
+                area.bottom - ((y - y_min) / (y_max - y_min)) as f32 * area.height(),
+            ]
+        };
+        let grid = self.text_color.gamma_multiply(0.25);
+
+        canvas.rect(Rect { left: 0.0, top: 0.0, right: width, bottom: height }, Some(self.background), None, 0.0);
+
+        let (step, decimals) = tick_step((x_min, x_max));
+        for tick in ticks((x_min, x_max), step) {
+            let [x, _] = to_image([tick, y_min]);
+            canvas.polyline(&[[x, area.top], [x, area.bottom]], grid, scale, None);
+            canvas.text([x, area.bottom + 18.0 * scale], TICK_SIZE * scale, self.text_color, Anchor::Middle, &format!("{tick:.decimals$}"));
+        }
+        let (step, decimals) = tick_step((y_min, y_max));
+        for tick in ticks((y_min, y_max), step) {
+            let [_, y] = to_image([x_min, tick]);
+            canvas.polyline(&[[area.left, y], [area.right, y]], grid, scale, None);
+            canvas.text([area.left - 6.0 * scale, y + 4.0 * scale], TICK_SIZE * scale, self.text_color, Anchor::End, &format!("{tick:.decimals$}"));
+        }
+        canvas.rect(area, None, Some(self.text_color.gamma_multiply(0.5)), scale);
+        canvas.text([(area.left + area.right) / 2.0, height - 15.0 * scale], LABEL_SIZE * scale, self.text_color, Anchor::Middle, &self.x_label);
+        canvas.text([area.left, area.top - 14.0 * scale], LABEL_SIZE * scale, self.text_color, Anchor::Start, &self.y_label);
+
+        canvas.clip(Some(area));
+        for line in &self.lines {
+            let points: Vec<[f32; 2]> = line.points.iter()
+                .filter(|[x, y]| x.is_finite() && y.is_finite())
+                .map(|point| to_image(*point))
+                .collect();
+            canvas.polyline(&points, line.color, LINE_WIDTH * scale, dash(line.style, scale));
+        }
+        for marker in &self.markers {
+            canvas.marker(to_image(marker.point), MARKER_RADIUS * scale, marker.color, marker.shape);
+        }
+        canvas.clip(None);
+
+        self.draw_legend(canvas, area, scale);
+    }
+
+    /// Every named line and marker once, sorted by name like the legend on screen.
+    fn draw_legend(&self, canvas: &mut impl Canvas, area: Rect, scale: f32) {
+        let mut entries = BTreeMap::new();
+        for line in self.lines.iter().filter(|line| !line.name.is_empty()) {
+            entries.entry(line.name.as_str()).or_insert(LegendEntry::Line(line.color, line.style));
+        }
+        for marker in self.markers.iter().filter(|marker| !marker.name.is_empty()) {
+            entries.entry(marker.name.as_str()).or_insert(LegendEntry::Marker(marker.color, marker.shape));
+        }
+        if entries.is_empty() {
+            return;
+        }
+
+        let size = TICK_SIZE * scale;
+        let row = 18.0 * scale;
+        let padding = 8.0 * scale;
+        let swatch = 20.0 * scale;
+        let text_width = entries.keys().map(|name| canvas.text_width(name, size)).fold(0.0, f32::max);
+        let right = area.right - 10.0 * scale;
+        let left = right - text_width - swatch - 3.0 * padding;
+        let top = area.top + 10.0 * scale;
+        let bottom = top + entries.len() as f32 * row + padding;
+        canvas.rect(Rect { left, top, right, bottom }, Some(self.background.gamma_multiply(0.85)), Some(self.text_color.gamma_multiply(0.5)), scale);
+
+        for (i, (name, entry)) in entries.into_iter().enumerate() {
+            let y = top + padding / 2.0 + (i as f32 + 0.5) * row;
+            let x = left + padding;
+            match entry {
+                LegendEntry::Line(color, style) => canvas.polyline(&[[x, y], [x + swatch, y]], color, LINE_WIDTH * scale, dash(style, scale)),
+                LegendEntry::Marker(color, shape) => canvas.marker([x + swatch / 2.0, y], MARKER_RADIUS * scale, color, shape),
+            }
+            canvas.text([x + swatch + padding, y + size * 0.35], size, self.text_color, Anchor::Start, name);
+        }
+    }
+}
+
+enum LegendEntry {
+    Line(Color32, LineStyle),
+    Marker(Color32, MarkerShape),
+}
+
+/// On and off lengths of a line style, like `egui_plot` draws them.
+fn dash(style: LineStyle, scale: f32) -> Option<[f32; 2]> {
+    match style {
+        LineStyle::Solid => None,
+        LineStyle::Dashed { length } => Some([length * scale, length * scale / 2.0]),
+        // Round caps turn the tiny dashes into dots.
+        LineStyle::Dotted { spacing } => Some([0.01, spacing * scale]),
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Rect {
+    left: f32,
+    top: f32,
+    right: f32,
+    bottom: f32,
+}
+
+impl Rect {
+    fn width(&self) -> f32 {
+        self.right - self.left
+    }
+
+    fn height(&self) -> f32 {
+        self.bottom - self.top
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Anchor {
+    Start,
+    Middle,
+    End,
+}
+
+/// What the plot is drawn on. Coordinates are in pixels from the top left
+/// corner, text is placed by its baseline.
+trait Canvas {
+    fn rect(&mut self, rect: Rect, fill: Option<Color32>, stroke: Option<Color32>, width: f32);
+    /// `dash` holds the on and off lengths of a dashed line.
+    fn polyline(&mut self, points: &[[f32; 2]], color: Color32, width: f32, dash: Option<[f32; 2]>);
+    fn marker(&mut self, center: [f32; 2], radius: f32, color: Color32, shape: MarkerShape);
+    fn text(&mut self, position: [f32; 2], size: f32, color: Color32, anchor: Anchor, text: &str);
+    fn text_width(&self, text: &str, size: f32) -> f32;
+    /// Limits drawing to `rect` until it is called with `None`.
+    fn clip(&mut self, rect: Option<Rect>);
+}
+
+/// The proportional font egui draws text in, so images need no font of
+/// their own and PNGs look like the screen.
+fn font() -> anyhow::Result<FontArc> {
+    let fonts = egui::FontDefinitions::default();
+    let data = fonts.font_data.get("Ubuntu-Light").context("The default font is missing")?;
+    Ok(FontArc::try_from_vec(data.font.to_vec())?)
+}
+
+/// Width of `text` set in `font` at `size` pixels.
+fn text_width(font: &FontArc, text: &str, size: f32) -> f32 {
+    let font = font.as_scaled(PxScale::from(size));
+    let mut previous = None;
+    let mut width = 0.0;
+    for c in text.chars() {
+        let id = font.glyph_id(c);
+        if let Some(previous) = previous {
+            width += font.kern(previous, id);
+        }
+        width += font.h_advance(id);
+        previous = Some(id);
+    }
+    width
+}
+
+/// The corners of a diamond, every other shape is drawn as a circle.
+fn diamond([x, y]: [f32; 2], radius: f32) -> [[f32; 2]; 4] {
+    [[x, y - radius], [x + radius, y], [x, y + radius], [x - radius, y]]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image() -> PlotImage {
+        PlotImage {
+            x_label: "Extension (mm)".to_owned(),
+            y_label: "Force <N> & more".to_owned(),
+            bounds: PlotBounds::from_min_max([0.0, 0.0], [10.0, 100.0]),
+            lines: vec![PlotLine::new("Test", Color32::RED, vec![[0.0, 0.0], [5.0, 80.0], [10.0, f64::NAN]])],
+            markers: vec![PlotMarker::new("Fm", Color32::GREEN, MarkerShape::Diamond, [5.0, 80.0])],
+            background: Color32::BLACK,
+            text_color: Color32::WHITE,
+        }
+    }
+
+    #[test]
+    fn png_has_the_asked_size() {
+        let png = image().encode(ImageFormat::Png, 400, 300).unwrap();
+
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+        // Width and height open the IHDR chunk.
+        assert_eq!(&png[16..24], [0, 0, 1, 144, 0, 0, 1, 44]);
+    }
+
+    #[test]
+    fn png_size_is_limited() {
+        assert!(image().encode(ImageFormat::Png, MAX_SIZE + 1, 300).is_err());
+        assert!(image().encode(ImageFormat::Png, 0, 300).is_err());
+    }
+
+    #[test]
+    fn svg_escapes_text_and_closes_clips() {
+        let svg = String::from_utf8(image().encode(ImageFormat::Svg, 400, 300).unwrap()).unwrap();
+
+        assert!(svg.contains("width=\"400\" height=\"300\""));
+        assert!(svg.contains(">Force &lt;N&gt; &amp; more</text>"));
+        assert!(svg.contains("<polygon points="));
+        assert_eq!(svg.matches("<g clip-path").count(), svg.matches("</g>").count());
+        assert!(!svg.contains("NaN"));
+        assert!(svg.ends_with("</svg>\n"));
+    }
+}
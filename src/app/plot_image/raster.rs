@@ -0,0 +1,151 @@
+//! Draws the plot into pixels on the CPU and encodes them as a PNG.
+
+use ab_glyph::{point, Font as _, FontArc, PxScale, ScaleFont as _};
+use anyhow::Context as _;
+use egui::Color32;
+use egui_plot::MarkerShape;
+use tiny_skia::{FillRule, LineCap, LineJoin, Mask, Paint, PathBuilder, Pixmap, PremultipliedColorU8, Stroke, StrokeDash, Transform};
+
+use super::{diamond, text_width, Anchor, Canvas, Rect, MAX_SIZE};
+
+pub struct Raster {
+    pixmap: Pixmap,
+    font: FontArc,
+    clip: Option<Mask>,
+}
+
+impl Raster {
+    pub fn new(width: u32, height: u32, font: FontArc) -> anyhow::Result<Self> {
+        anyhow::ensure!(width <= MAX_SIZE && height <= MAX_SIZE, "Images are at most {MAX_SIZE} pixels wide and high");
+        let pixmap = Pixmap::new(width, height).context("The image has no pixels")?;
+        Ok(Self { pixmap, font, clip: None })
+    }
+
+    pub fn finish(self) -> anyhow::Result<Vec<u8>> {
+        Ok(self.pixmap.encode_png()?)
+    }
+}
+
+impl Canvas for Raster {
+    fn rect(&mut self, rect: Rect, fill: Option<Color32>, stroke: Option<Color32>, width: f32) {
+        let Some(area) = tiny_skia::Rect::from_ltrb(rect.left, rect.top, rect.right, rect.bottom) else {
+            return;
+        };
+
+        if let Some(fill) = fill {
+            self.pixmap.fill_rect(area, &paint(fill), Transform::identity(), self.clip.as_ref());
+        }
+        if let Some(stroke) = stroke {
+            let path = PathBuilder::from_rect(area);
+            let line = Stroke { width, ..Default::default() };
+            self.pixmap.stroke_path(&path, &paint(stroke), &line, Transform::identity(), self.clip.as_ref());
+        }
+    }
+
+    fn polyline(&mut self, points: &[[f32; 2]], color: Color32, width: f32, dash: Option<[f32; 2]>) {
+        let mut builder = PathBuilder::new();
+        for (i, [x, y]) in points.iter().enumerate() {
+            if i == 0 {
+                builder.move_to(*x, *y);
+            } else {
+                builder.line_to(*x, *y);
+            }
+        }
+        let Some(path) = builder.finish() else {
+            return;
+        };
+
+        let stroke = Stroke {
+            width,
+            line_cap: LineCap::Round,
+            line_join: LineJoin::Round,
+            dash: dash.and_then(|[on, off]| StrokeDash::new(vec![on, off], 0.0)),
+            ..Default::default()
+        };
+        self.pixmap.stroke_path(&path, &paint(color), &stroke, Transform::identity(), self.clip.as_ref());
+    }
+
+    fn marker(&mut self, center: [f32; 2], radius: f32, color: Color32, shape: MarkerShape) {
+        let path = match shape {
+            MarkerShape::Diamond => {
+                let mut builder = PathBuilder::new();
+                let [first, rest @ ..] = diamond(center, radius);
+                builder.move_to(first[0], first[1]);
+                for [x, y] in rest {
+                    builder.line_to(x, y);
+                }
+                builder.close();
+                builder.finish()
+            },
+            _ => PathBuilder::from_circle(center[0], center[1], radius),
+        };
+
+        if let Some(path) = path {
+            self.pixmap.fill_path(&path, &paint(color), FillRule::Winding, Transform::identity(), self.clip.as_ref());
+        }
+    }
+
+    fn text(&mut self, position: [f32; 2], size: f32, color: Color32, anchor: Anchor, text: &str) {
+        let font = self.font.as_scaled(PxScale::from(size));
+        let width = text_width(&self.font, text, size);
+        let [x, y] = position;
+        let mut caret = match anchor {
+            Anchor::Start => x,
+            Anchor::Middle => x - width / 2.0,
+            Anchor::End => x - width,
+        };
+
+        let [r, g, b, a] = color.to_srgba_unmultiplied();
+        let (image_width, image_height) = (self.pixmap.width() as i32, self.pixmap.height() as i32);
+        let pixels = self.pixmap.pixels_mut();
+        let mut previous = None;
+        for c in text.chars() {
+            let id = font.glyph_id(c);
+            if let Some(previous) = previous {
+                caret += font.kern(previous, id);
+            }
+            let glyph = id.with_scale_and_position(size, point(caret, y));
+            caret += font.h_advance(id);
+            previous = Some(id);
+
+            let Some(outline) = self.font.outline_glyph(glyph) else {
+                continue;
+            };
+            let bounds = outline.px_bounds();
+            outline.draw(|gx, gy, coverage| {
+                let (px, py) = (bounds.min.x as i32 + gx as i32, bounds.min.y as i32 + gy as i32);
+                if px < 0 || py < 0 || px >= image_width || py >= image_height {
+                    return;
+                }
+
+                // Source over on premultiplied pixels.
+                let alpha = a as f32 / 255.0 * coverage.clamp(0.0, 1.0);
+                let pixel = &mut pixels[(py * image_width + px) as usize];
+                let blend = |source: u8, target: u8| (source as f32 * alpha + target as f32 * (1.0 - alpha)).round() as u8;
+                if let Some(blended) = PremultipliedColorU8::from_rgba(blend(r, pixel.red()), blend(g, pixel.green()), blend(b, pixel.blue()), blend(255, pixel.alpha())) {
+                    *pixel = blended;
+                }
+            });
+        }
+    }
+
+    fn text_width(&self, text: &str, size: f32) -> f32 {
+        text_width(&self.font, text, size)
+    }
+
+    fn clip(&mut self, rect: Option<Rect>) {
+        self.clip = rect.and_then(|rect| {
+            let area = tiny_skia::Rect::from_ltrb(rect.left, rect.top, rect.right, rect.bottom)?;
+            let mut mask = Mask::new(self.pixmap.width(), self.pixmap.height())?;
+            mask.fill_path(&PathBuilder::from_rect(area), FillRule::Winding, false, Transform::identity());
+            Some(mask)
+        });
+    }
+}
+
+fn paint(color: Color32) -> Paint<'static> {
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    let mut paint = Paint { anti_alias: true, ..Default::default() };
+    paint.set_color_rgba8(r, g, b, a);
+    paint
+}
@@ -0,0 +1,120 @@
+//! Draws the plot as an SVG document.
+
+use std::fmt::Write as _;
+
+use ab_glyph::FontArc;
+use egui::Color32;
+use egui_plot::MarkerShape;
+
+use super::{diamond, text_width, Anchor, Canvas, Rect};
+
+pub struct Svg {
+    out: String,
+    font: FontArc,
+    /// Clip paths defined so far, each needs a unique id.
+    clips: usize,
+}
+
+impl Svg {
+    pub fn new(width: u32, height: u32, font: FontArc) -> Self {
+        let out = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">\n"
+        );
+        Self { out, font, clips: 0 }
+    }
+
+    pub fn finish(mut self) -> String {
+        self.out.push_str("</svg>\n");
+        self.out
+    }
+}
+
+impl Canvas for Svg {
+    fn rect(&mut self, rect: Rect, fill: Option<Color32>, stroke: Option<Color32>, width: f32) {
+        let _ = writeln!(
+            self.out,
+            "<rect x=\"{:.2}\" y=\"{:.2}\" width=\"{:.2}\" height=\"{:.2}\" {} {} stroke-width=\"{width:.2}\"/>",
+            rect.left, rect.top, rect.width(), rect.height(), paint("fill", fill), paint("stroke", stroke),
+        );
+    }
+
+    fn polyline(&mut self, points: &[[f32; 2]], color: Color32, width: f32, dash: Option<[f32; 2]>) {
+        if points.len() < 2 {
+            return;
+        }
+
+        let _ = write!(self.out, "<polyline fill=\"none\" {} stroke-width=\"{width:.2}\" stroke-linecap=\"round\" stroke-linejoin=\"round\"", paint("stroke", Some(color)));
+        if let Some([on, off]) = dash {
+            let _ = write!(self.out, " stroke-dasharray=\"{on:.2} {off:.2}\"");
+        }
+        self.out.push_str(" points=\"");
+        for [x, y] in points {
+            let _ = write!(self.out, "{x:.2},{y:.2} ");
+        }
+        self.out.push_str("\"/>\n");
+    }
+
+    fn marker(&mut self, center: [f32; 2], radius: f32, color: Color32, shape: MarkerShape) {
+        let [x, y] = center;
+        match shape {
+            MarkerShape::Diamond => {
+                let points: Vec<String> = diamond(center, radius).iter().map(|[x, y]| format!("{x:.2},{y:.2}")).collect();
+                let _ = writeln!(self.out, "<polygon points=\"{}\" {}/>", points.join(" "), paint("fill", Some(color)));
+            },
+            _ => {
+                let _ = writeln!(self.out, "<circle cx=\"{x:.2}\" cy=\"{y:.2}\" r=\"{radius:.2}\" {}/>", paint("fill", Some(color)));
+            },
+        }
+    }
+
+    fn text(&mut self, position: [f32; 2], size: f32, color: Color32, anchor: Anchor, text: &str) {
+        let [x, y] = position;
+        let anchor = match anchor {
+            Anchor::Start => "start",
+            Anchor::Middle => "middle",
+            Anchor::End => "end",
+        };
+        let _ = writeln!(
+            self.out,
+            "<text x=\"{x:.2}\" y=\"{y:.2}\" font-family=\"Ubuntu, sans-serif\" font-size=\"{size:.2}\" text-anchor=\"{anchor}\" {}>{}</text>",
+            paint("fill", Some(color)), escape(text),
+        );
+    }
+
+    fn text_width(&self, text: &str, size: f32) -> f32 {
+        text_width(&self.font, text, size)
+    }
+
+    fn clip(&mut self, rect: Option<Rect>) {
+        match rect {
+            Some(rect) => {
+                self.clips += 1;
+                let _ = writeln!(
+                    self.out,
+                    "<clipPath id=\"clip{}\"><rect x=\"{:.2}\" y=\"{:.2}\" width=\"{:.2}\" height=\"{:.2}\"/></clipPath>\n<g clip-path=\"url(#clip{})\">",
+                    self.clips, rect.left, rect.top, rect.width(), rect.height(), self.clips,
+                );
+            },
+            None => self.out.push_str("</g>\n"),
+        }
+    }
+}
+
+/// `fill` or `stroke` attributes for `color`, `none` without one.
+fn paint(attribute: &str, color: Option<Color32>) -> String {
+    let Some(color) = color else {
+        return format!("{attribute}=\"none\"");
+    };
+
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    if a == 255 {
+        format!("{attribute}=\"#{r:02x}{g:02x}{b:02x}\"")
+    } else {
+        format!("{attribute}=\"#{r:02x}{g:02x}{b:02x}\" {attribute}-opacity=\"{:.3}\"", a as f32 / 255.0)
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
@@ -0,0 +1,322 @@
+//! Printable test reports, laid out as a list of blocks and written as PDF.
+
+use std::io::Write;
+
+mod pdf;
+mod template;
+
+use self::pdf::{Document, Font, Page, BLACK, GREY, PAGE_HEIGHT, PAGE_WIDTH};
+pub use self::template::{result_key, Language, ReportTemplate, Section};
+
+const LOGO: &[u8] = include_bytes!("../../assets/logo.png");
+
+const MARGIN: f64 = 50.0;
+/// Space kept free at the bottom of every page for the footer.
+const FOOTER_HEIGHT: f64 = 20.0;
+/// Where the value column of name–value rows starts.
+const VALUE_COLUMN: f64 = 190.0;
+/// Width of the first column of tables, the other columns share the rest.
+const NAME_COLUMN: f64 = 165.0;
+const ROW_HEIGHT: f64 = 14.0;
+const TEXT_SIZE: f64 = 9.5;
+const HEADING_SIZE: f64 = 12.0;
+const CHART_HEIGHT: f64 = 260.0;
+/// More points than this are thinned out, the printed curve looks the same.
+const MAX_CHART_POINTS: usize = 2000;
+
+const CURVE_COLOR: pdf::Color = [0.12, 0.31, 0.62];
+const MARKER_COLOR: pdf::Color = [0.85, 0.33, 0.1];
+
+/// A curve with labelled points on it, in the units it is shown in.
+pub struct Chart {
+    pub x_label: String,
+    pub y_label: String,
+    pub curve: Vec<[f64; 2]>,
+    pub markers: Vec<(String, [f64; 2])>,
+}
+
+pub enum Block {
+    /// A heading with name–value rows under it, the values are printed as
+    /// they are.
+    Rows(String, Vec<(String, String)>),
+    Chart(Chart),
+    /// A heading with a table under it, the first row holds the column
+    /// names and is the only one translated.
+    Table(String, Vec<Vec<String>>),
+    /// Lines for signing off the report.
+    Signature { operator: String },
+}
+
+/// A report in English, translated into `language` as it is written.
+/// Values are printed as they are, so what was typed in is never
+/// translated and values like the test mode come translated already.
+pub struct Report {
+    pub title: String,
+    /// Printed under the title, e.g. the date of the test.
+    pub subtitle: String,
+    /// Printed at the top of every page.
+    pub header: String,
+    /// Printed at the bottom of every page.
+    pub footer: String,
+    pub language: Language,
+    pub blocks: Vec<Block>,
+}
+
+impl Report {
+    pub fn write(&self, out: &mut impl Write) -> anyhow::Result<()> {
+        let mut layout = Layout { language: self.language, ..Default::default() };
+        layout.new_page();
+        let text = |text: &str| self.language.translate(text);
+
+        let logo = layout.document.add_image(&image::load_from_memory(LOGO)?.to_rgba8())?;
+        let top = PAGE_HEIGHT - MARGIN;
+        layout.page().image(logo, MARGIN, top - 25.0, 150.0, 25.0);
+        layout.page().text(MARGIN, top - 55.0, 16.0, Font::Bold, &text(&self.title));
+        layout.page().text(MARGIN, top - 72.0, TEXT_SIZE, Font::Regular, &self.subtitle);
+        layout.y = top - 90.0;
+
+        for block in &self.blocks {
+            match block {
+                Block::Rows(heading, rows) => layout.rows(heading, rows),
+                Block::Chart(chart) => layout.chart(chart),
+                Block::Table(heading, rows) => layout.table(heading, rows),
+                Block::Signature { operator } => layout.signature(operator),
+            }
+        }
+
+        let pages = layout.document.pages.len();
+        for (i, page) in layout.document.pages.iter_mut().enumerate() {
+            page.text(MARGIN, PAGE_HEIGHT - MARGIN + 16.0, 8.0, Font::Regular, &self.header);
+            page.line([MARGIN, MARGIN], [PAGE_WIDTH - MARGIN, MARGIN], 0.5, GREY);
+            page.text(MARGIN, MARGIN - 12.0, 8.0, Font::Regular, &self.footer);
+            page.text(PAGE_WIDTH - MARGIN - 50.0, MARGIN - 12.0, 8.0, Font::Regular, &format!("{} {} {} {pages}", text("Page"), i + 1, text("of")));
+        }
+
+        layout.document.write(out)
+    }
+}
+
+/// Places blocks from the top of the page down, starting a new page when
+/// one doesn't fit.
+#[derive(Default)]
+struct Layout {
+    document: Document,
+    language: Language,
+    /// Top of the free space on the current page.
+    y: f64,
+}
+
+impl Layout {
+    fn page(&mut self) -> &mut Page {
+        self.document.pages.last_mut().expect("the layout starts with a page")
+    }
+
+    fn new_page(&mut self) {
+        self.document.pages.push(Page::default());
+        self.y = PAGE_HEIGHT - MARGIN;
+    }
+
+    /// Makes sure `height` fits on the current page.
+    fn reserve(&mut self, height: f64) {
+        if self.y - height < MARGIN + FOOTER_HEIGHT {
+            self.new_page();
+        }
+    }
+
+    fn text(&self, text: &str) -> String {
+        self.language.translate(text)
+    }
+
+    fn heading(&mut self, heading: &str) {
+        // Keep the heading together with at least its first row.
+        self.reserve(24.0 + ROW_HEIGHT);
+        self.y -= 18.0;
+        let y = self.y;
+        let heading = self.text(heading);
+        self.page().text(MARGIN, y, HEADING_SIZE, Font::Bold, &heading);
+        self.page().line([MARGIN, y - 4.0], [PAGE_WIDTH - MARGIN, y - 4.0], 0.5, GREY);
+        self.y -= 6.0;
+    }
+
+    fn rows(&mut self, heading: &str, rows: &[(String, String)]) {
+        if rows.is_empty() {
+            return;
+        }
+        self.heading(heading);
+
+        for (name, value) in rows {
+            let name = self.text(name);
+            let lines = wrap(value, 60);
+            for (i, line) in lines.iter().enumerate() {
+                self.reserve(ROW_HEIGHT);
+                self.y -= ROW_HEIGHT;
+                let y = self.y;
+                if i == 0 {
+                    self.page().text(MARGIN, y, TEXT_SIZE, Font::Regular, &name);
+                }
+                self.page().text(MARGIN + VALUE_COLUMN, y, TEXT_SIZE, Font::Regular, line);
+            }
+        }
+    }
+
+    fn table(&mut self, heading: &str, rows: &[Vec<String>]) {
+        // Only column names is no table.
+        if rows.len() < 2 {
+            return;
+        }
+        self.heading(heading);
+
+        for (i, row) in rows.iter().enumerate() {
+            let font = if i == 0 { Font::Bold } else { Font::Regular };
+            let width = (PAGE_WIDTH - 2.0 * MARGIN - NAME_COLUMN) / (row.len().max(2) - 1) as f64;
+            let cells: Vec<(f64, Vec<String>)> = row.iter()
+                .enumerate()
+                .map(|(column, cell)| {
+                    let (x, width) = if column == 0 { (0.0, NAME_COLUMN) } else { (NAME_COLUMN + (column - 1) as f64 * width, width) };
+                    let cell = if i == 0 { self.text(cell) } else { cell.clone() };
+                    // About two characters per text size of width.
+                    (MARGIN + x, wrap(&cell, (2.0 * width / TEXT_SIZE) as usize))
+                })
+                .collect();
+
+            let lines = cells.iter().map(|(_, lines)| lines.len()).max().unwrap_or(1);
+            self.reserve(lines as f64 * ROW_HEIGHT);
+            let top = self.y;
+            for (x, cell) in cells {
+                for (line, text) in cell.iter().enumerate() {
+                    self.page().text(x, top - (line + 1) as f64 * ROW_HEIGHT, TEXT_SIZE, font, text);
+                }
+            }
+            self.y -= lines as f64 * ROW_HEIGHT;
+        }
+    }
+
+    fn chart(&mut self, chart: &Chart) {
+        self.reserve(CHART_HEIGHT + 12.0);
+        self.y -= 12.0;
+
+        // Plot area, leaving room for the tick labels and axis labels.
+        let left = MARGIN + 55.0;
+        let right = PAGE_WIDTH - MARGIN - 10.0;
+        let top = self.y - 18.0;
+        let bottom = self.y - CHART_HEIGHT + 30.0;
+
+        let points = chart.curve.iter().chain(chart.markers.iter().map(|(_, point)| point));
+        let (x_range, y_range) = bounds(points);
+        let to_page = |[x, y]: [f64; 2]| {
+            [
+                left + (x - x_range.0) / (x_range.1 - x_range.0) * (right - left),
+                bottom + (y - y_range.0) / (y_range.1 - y_range.0) * (top - bottom),
+            ]
+        };
+
+        let (x_label, y_label) = (self.text(&chart.x_label), self.text(&chart.y_label));
+        let markers: Vec<(String, [f64; 2])> = chart.markers.iter().map(|(name, point)| (self.text(name), *point)).collect();
+
+        let page = self.page();
+        page.text(left - 50.0, top + 8.0, TEXT_SIZE, Font::Regular, &y_label);
+        page.text((left + right) / 2.0 - 30.0, bottom - 28.0, TEXT_SIZE, Font::Regular, &x_label);
+
+        let (step, decimals) = tick_step(x_range);
+        for tick in ticks(x_range, step) {
+            let [x, _] = to_page([tick, y_range.0]);
+            page.line([x, bottom], [x, top], 0.25, GREY);
+            page.text(x - 8.0, bottom - 12.0, 8.0, Font::Regular, &format!("{tick:.decimals$}"));
+        }
+        let (step, decimals) = tick_step(y_range);
+        for tick in ticks(y_range, step) {
+            let [_, y] = to_page([x_range.0, tick]);
+            page.line([left, y], [right, y], 0.25, GREY);
+            let label = format!("{tick:.decimals$}");
+            page.text(left - 6.0 - 4.6 * label.len() as f64, y - 3.0, 8.0, Font::Regular, &label);
+        }
+        page.rect(left, bottom, right - left, top - bottom, 0.75, BLACK);
+
+        let stride = chart.curve.len() / MAX_CHART_POINTS + 1;
+        let curve: Vec<[f64; 2]> = chart.curve.iter().step_by(stride).copied().map(to_page).collect();
+        page.clipped(left, bottom, right - left, top - bottom, |page| {
+            page.polyline(&curve, 1.0, CURVE_COLOR);
+        });
+
+        for (name, point) in markers {
+            let [x, y] = to_page(point);
+            page.dot([x, y], 2.5, MARKER_COLOR);
+            page.text(x + 4.0, y + 4.0, 8.0, Font::Bold, &name);
+        }
+
+        self.y -= CHART_HEIGHT;
+    }
+
+    fn signature(&mut self, operator: &str) {
+        // The signature lines stay on one page with their heading.
+        self.reserve(24.0 + 4.0 * 24.0);
+        self.heading("Sign-off");
+
+        for (label, value) in [("Tested by", operator), ("Approved by", ""), ("Signature", ""), ("Date", "")] {
+            self.y -= 24.0;
+            let y = self.y;
+            let label = self.text(label);
+            self.page().text(MARGIN, y, TEXT_SIZE, Font::Regular, &label);
+            self.page().text(MARGIN + VALUE_COLUMN, y + 2.0, TEXT_SIZE, Font::Regular, value);
+            self.page().line([MARGIN + VALUE_COLUMN, y - 2.0], [MARGIN + VALUE_COLUMN + 200.0, y - 2.0], 0.5, BLACK);
+        }
+    }
+}
+
+/// X and Y ranges spanning `points` and zero, with a little room on top.
+fn bounds<'a>(points: impl Iterator<Item = &'a [f64; 2]>) -> ((f64, f64), (f64, f64)) {
+    let (mut x, mut y) = ((0.0, 0.0), (0.0, 0.0));
+    for [px, py] in points.filter(|[px, py]| px.is_finite() && py.is_finite()) {
+        x = (f64::min(x.0, *px), f64::max(x.1, *px));
+        y = (f64::min(y.0, *py), f64::max(y.1, *py));
+    }
+
+    let pad = |(min, max): (f64, f64)| {
+        let span = if max > min { max - min } else { 1.0 };
+        (min, max + 0.05 * span)
+    };
+    (pad(x), pad(y))
+}
+
+/// A round tick spacing giving about five ticks, and the decimals it needs.
+pub fn tick_step((min, max): (f64, f64)) -> (f64, usize) {
+    let rough = (max - min) / 5.0;
+    let magnitude = 10f64.powf(rough.log10().floor());
+    let step = [1.0, 2.0, 5.0, 10.0]
+        .into_iter()
+        .map(|factor| factor * magnitude)
+        .find(|step| *step >= rough)
+        .unwrap_or(10.0 * magnitude);
+
+    (step, (-step.log10().floor()).max(0.0) as usize)
+}
+
+pub fn ticks((min, max): (f64, f64), step: f64) -> impl Iterator<Item = f64> {
+    let first = (min / step).ceil() as i64;
+    let last = (max / step).floor() as i64;
+    (first..=last).map(move |i| i as f64 * step)
+}
+
+/// Breaks text into lines of at most about `width` characters, at spaces.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            if !line.is_empty() && line.chars().count() + word.chars().count() >= width {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+        lines.push(line);
+    }
+
+    if lines.is_empty() {
+        lines.push(String::new());
+    }
+    lines
+}
@@ -0,0 +1,357 @@
+//! Just enough PDF to lay out a report: text in the standard Helvetica and
+//! Symbol fonts, lines, rectangles and images. Coordinates are in points
+//! from the bottom left corner of the page.
+
+use std::{fmt::Write as _, io::Write};
+
+use flate2::{write::ZlibEncoder, Compression};
+
+/// A4 portrait, in points.
+pub const PAGE_WIDTH: f64 = 595.28;
+pub const PAGE_HEIGHT: f64 = 841.89;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Font {
+    Regular,
+    Bold,
+}
+
+impl Font {
+    fn resource(&self) -> &'static str {
+        match self {
+            Font::Regular => "F1",
+            Font::Bold => "F2",
+        }
+    }
+}
+
+/// Greek letters are taken from the Symbol font.
+const SYMBOL_FONT: &str = "F3";
+
+pub type Color = [f64; 3];
+
+pub const BLACK: Color = [0.0, 0.0, 0.0];
+pub const GREY: Color = [0.6, 0.6, 0.6];
+
+/// An image added to the document, drawn with `Page::image`.
+#[derive(Debug, Clone, Copy)]
+pub struct ImageId(usize);
+
+struct Image {
+    width: u32,
+    height: u32,
+    /// Zlib compressed RGB samples.
+    rgb: Vec<u8>,
+    /// Zlib compressed alpha samples, if any are not opaque.
+    alpha: Option<Vec<u8>>,
+}
+
+#[derive(Default)]
+pub struct Page {
+    content: String,
+}
+
+impl Page {
+    /// Draws `text` with its baseline starting at `x`, `y`.
+    pub fn text(&mut self, x: f64, y: f64, size: f64, font: Font, text: &str) {
+        let _ = write!(self.content, "BT {x:.2} {y:.2} Td");
+
+        let mut current = None;
+        for (symbol, run) in runs(text) {
+            let resource = if symbol { SYMBOL_FONT } else { font.resource() };
+            if current != Some(resource) {
+                let _ = write!(self.content, " /{resource} {size:.1} Tf");
+                current = Some(resource);
+            }
+            let _ = write!(self.content, " ({}) Tj", escape(&run));
+        }
+
+        self.content.push_str(" ET\n");
+    }
+
+    /// A line through `points`.
+    pub fn polyline(&mut self, points: &[[f64; 2]], width: f64, color: Color) {
+        let Some(([x, y], rest)) = points.split_first() else {
+            return;
+        };
+
+        let _ = write!(self.content, "{} {width:.2} w {x:.2} {y:.2} m", stroke_color(color));
+        for [x, y] in rest {
+            let _ = write!(self.content, " {x:.2} {y:.2} l");
+        }
+        self.content.push_str(" S\n");
+    }
+
+    pub fn line(&mut self, from: [f64; 2], to: [f64; 2], width: f64, color: Color) {
+        self.polyline(&[from, to], width, color);
+    }
+
+    pub fn rect(&mut self, x: f64, y: f64, width: f64, height: f64, line_width: f64, color: Color) {
+        let _ = writeln!(self.content, "{} {line_width:.2} w {x:.2} {y:.2} {width:.2} {height:.2} re S", stroke_color(color));
+    }
+
+    /// A filled circle, drawn as four Bézier curves.
+    pub fn dot(&mut self, [x, y]: [f64; 2], radius: f64, color: Color) {
+        let [r, g, b] = color;
+        let k = radius * 0.552_284_75;
+        let _ = writeln!(
+            self.content,
+            "{r:.3} {g:.3} {b:.3} rg {:.2} {y:.2} m \
+             {:.2} {:.2} {:.2} {:.2} {x:.2} {:.2} c \
+             {:.2} {:.2} {:.2} {:.2} {:.2} {y:.2} c \
+             {:.2} {:.2} {:.2} {:.2} {x:.2} {:.2} c \
+             {:.2} {:.2} {:.2} {:.2} {:.2} {y:.2} c f 0 g",
+            x + radius,
+            x + radius, y + k, x + k, y + radius, y + radius,
+            x - k, y + radius, x - radius, y + k, x - radius,
+            x - radius, y - k, x - k, y - radius, y - radius,
+            x + k, y - radius, x + radius, y - k, x + radius,
+        );
+    }
+
+    /// Limits everything drawn in `draw` to a rectangle.
+    pub fn clipped(&mut self, x: f64, y: f64, width: f64, height: f64, draw: impl FnOnce(&mut Page)) {
+        let _ = writeln!(self.content, "q {x:.2} {y:.2} {width:.2} {height:.2} re W n");
+        draw(self);
+        self.content.push_str("Q\n");
+    }
+
+    pub fn image(&mut self, image: ImageId, x: f64, y: f64, width: f64, height: f64) {
+        let _ = writeln!(self.content, "q {width:.2} 0 0 {height:.2} {x:.2} {y:.2} cm /Im{} Do Q", image.0);
+    }
+}
+
+#[derive(Default)]
+pub struct Document {
+    pub pages: Vec<Page>,
+    images: Vec<Image>,
+}
+
+impl Document {
+    pub fn add_image(&mut self, image: &image::RgbaImage) -> anyhow::Result<ImageId> {
+        let rgb: Vec<u8> = image.pixels().flat_map(|pixel| [pixel[0], pixel[1], pixel[2]]).collect();
+        let alpha: Vec<u8> = image.pixels().map(|pixel| pixel[3]).collect();
+
+        self.images.push(Image {
+            width: image.width(),
+            height: image.height(),
+            rgb: compress(&rgb)?,
+            alpha: if alpha.iter().all(|a| *a == u8::MAX) { None } else { Some(compress(&alpha)?) },
+        });
+
+        Ok(ImageId(self.images.len() - 1))
+    }
+
+    /// Writes the document. Objects 1 and 2 are the catalog and page tree,
+    /// the fonts, images and pages follow.
+    pub fn write(&self, out: &mut impl Write) -> anyhow::Result<()> {
+        let mut objects: Vec<Vec<u8>> = vec![
+            b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+            Vec::new(), // The page tree, once the page numbers are known.
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>".to_vec(),
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>".to_vec(),
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Symbol >>".to_vec(),
+        ];
+
+        let mut image_resources = String::new();
+        for (i, image) in self.images.iter().enumerate() {
+            let smask = match &image.alpha {
+                Some(alpha) => {
+                    objects.push(stream(
+                        &format!("/Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceGray /BitsPerComponent 8 /Filter /FlateDecode", image.width, image.height),
+                        alpha,
+                    ));
+                    format!(" /SMask {} 0 R", objects.len())
+                },
+                None => String::new(),
+            };
+            objects.push(stream(
+                &format!("/Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceRGB /BitsPerComponent 8 /Filter /FlateDecode{smask}", image.width, image.height),
+                &image.rgb,
+            ));
+            let _ = write!(image_resources, " /Im{i} {} 0 R", objects.len());
+        }
+
+        let mut kids = Vec::new();
+        for page in &self.pages {
+            objects.push(stream("/Filter /FlateDecode", &compress(page.content.as_bytes())?));
+            let content = objects.len();
+            objects.push(format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {PAGE_WIDTH} {PAGE_HEIGHT}] /Contents {content} 0 R \
+                 /Resources << /Font << /F1 3 0 R /F2 4 0 R /F3 5 0 R >> /XObject <<{image_resources} >> >> >>"
+            ).into_bytes());
+            kids.push(format!("{} 0 R", objects.len()));
+        }
+        objects[1] = format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), kids.len()).into_bytes();
+
+        let mut pdf = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+        let mut offsets = Vec::new();
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            writeln!(pdf, "{} 0 obj", i + 1)?;
+            pdf.extend_from_slice(object);
+            pdf.extend_from_slice(b"\nendobj\n");
+        }
+
+        let xref = pdf.len();
+        write!(pdf, "xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1)?;
+        for offset in offsets {
+            writeln!(pdf, "{offset:010} 00000 n ")?;
+        }
+        write!(pdf, "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n", objects.len() + 1)?;
+
+        out.write_all(&pdf)?;
+        Ok(())
+    }
+}
+
+fn stream(dictionary: &str, data: &[u8]) -> Vec<u8> {
+    let mut object = format!("<< {dictionary} /Length {} >>\nstream\n", data.len()).into_bytes();
+    object.extend_from_slice(data);
+    object.extend_from_slice(b"\nendstream");
+    object
+}
+
+fn compress(data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data)?;
+    Ok(encoder.finish()?)
+}
+
+fn stroke_color([r, g, b]: Color) -> String {
+    format!("{r:.3} {g:.3} {b:.3} RG")
+}
+
+/// Splits text into runs of Helvetica and Symbol font bytes.
+fn runs(text: &str) -> Vec<(bool, Vec<u8>)> {
+    let mut runs: Vec<(bool, Vec<u8>)> = Vec::new();
+
+    for c in text.chars() {
+        let (symbol, byte) = match symbol_code(c) {
+            Some(byte) => (true, byte),
+            None => (false, win_ansi_code(c)),
+        };
+        match runs.last_mut() {
+            Some((run_symbol, run)) if *run_symbol == symbol => run.push(byte),
+            _ => runs.push((symbol, vec![byte])),
+        }
+    }
+
+    runs
+}
+
+/// The byte of `c` in the WinAnsi encoding, `?` when it has none.
+fn win_ansi_code(c: char) -> u8 {
+    match c {
+        ' '..='~' | '\u{a0}'..='\u{ff}' => c as u8,
+        '€' => 0x80,
+        '…' => 0x85,
+        '‘' => 0x91,
+        '’' => 0x92,
+        '“' => 0x93,
+        '”' => 0x94,
+        '•' => 0x95,
+        '–' => 0x96,
+        '—' => 0x97,
+        '™' => 0x99,
+        _ => b'?',
+    }
+}
+
+/// The byte of a Greek letter in the Symbol font.
+fn symbol_code(c: char) -> Option<u8> {
+    let byte = match c {
+        'α' => b'a',
+        'β' => b'b',
+        'γ' => b'g',
+        'δ' => b'd',
+        'ε' => b'e',
+        'η' => b'h',
+        'θ' => b'q',
+        'λ' => b'l',
+        'μ' => b'm',
+        'ν' => b'n',
+        'π' => b'p',
+        'ρ' => b'r',
+        'σ' => b's',
+        'τ' => b't',
+        'φ' => b'f',
+        'ω' => b'w',
+        'Δ' => b'D',
+        'Σ' => b'S',
+        'Ω' => b'W',
+        _ => return None,
+    };
+    Some(byte)
+}
+
+/// Escapes bytes for a PDF string literal, anything outside printable
+/// ASCII as an octal escape.
+fn escape(bytes: &[u8]) -> String {
+    let mut escaped = String::new();
+    for byte in bytes {
+        match byte {
+            b'(' | b')' | b'\\' => {
+                escaped.push('\\');
+                escaped.push(*byte as char);
+            },
+            b' '..=b'~' => escaped.push(*byte as char),
+            _ => {
+                let _ = write!(escaped, "\\{byte:03o}");
+            },
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+        haystack.windows(needle.len()).position(|window| window == needle)
+    }
+
+    #[test]
+    fn xref_points_at_every_object() {
+        let mut document = Document::default();
+        document.pages.push(Page::default());
+        document.pages[0].text(50.0, 700.0, 12.0, Font::Regular, "Test");
+        let mut pdf = Vec::new();
+        document.write(&mut pdf).unwrap();
+
+        // Catalog, page tree, three fonts, the content stream and the page.
+        let xref = find(&pdf, b"xref\n").unwrap();
+        let table = String::from_utf8_lossy(&pdf[xref..]).into_owned();
+        let mut lines = table.lines().skip(1);
+        assert_eq!(lines.next(), Some("0 8"));
+        assert_eq!(lines.next(), Some("0000000000 65535 f "));
+        for object in 1..=7 {
+            let offset: usize = lines.next().unwrap()[..10].parse().unwrap();
+            assert!(pdf[offset..].starts_with(format!("{object} 0 obj\n").as_bytes()), "object {object}");
+        }
+        assert_eq!(lines.next(), Some("trailer"));
+        assert_eq!(lines.next(), Some("<< /Size 8 /Root 1 0 R >>"));
+        assert_eq!(lines.next(), Some("startxref"));
+        assert_eq!(lines.next(), Some(xref.to_string().as_str()));
+        assert!(find(&pdf, b"/Count 1").is_some());
+    }
+
+    #[test]
+    fn greek_letters_take_the_symbol_font() {
+        assert_eq!(runs("Rp0.2 (σ)"), [(false, b"Rp0.2 (".to_vec()), (true, b"s".to_vec()), (false, b")".to_vec())]);
+        assert_eq!(runs("Δε"), [(true, b"De".to_vec())]);
+    }
+
+    #[test]
+    fn characters_outside_win_ansi() {
+        assert_eq!(runs("5 µm – 20 °C"), [(false, b"5 \xb5m \x96 20 \xb0C".to_vec())]);
+        assert_eq!(runs("≥ 中"), [(false, b"? ?".to_vec())]);
+    }
+
+    #[test]
+    fn escape_parentheses_backslashes_and_bytes() {
+        assert_eq!(escape(b"a(b)\\c"), r"a\(b\)\\c");
+        assert_eq!(escape(b"20 \xb0C\n"), r"20 \260C\012");
+    }
+}
@@ -0,0 +1,250 @@
+//! Report templates: which sections a report has and in which order, which
+//! results it lists, its header and footer, language and units.
+
+use std::fmt::{self, Formatter};
+
+use strum_macros::EnumIter;
+
+use crate::app::units::Units;
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, EnumIter)]
+pub enum Section {
+    Test,
+    Specimen,
+    Chart,
+    Results,
+    Calibration,
+    Signature,
+}
+
+impl fmt::Display for Section {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Section::Test => write!(f, "Test information"),
+            Section::Specimen => write!(f, "Specimen"),
+            Section::Chart => write!(f, "Curve"),
+            Section::Results => write!(f, "Results"),
+            Section::Calibration => write!(f, "Calibration"),
+            Section::Signature => write!(f, "Sign-off"),
+        }
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone, Copy, PartialEq, EnumIter)]
+pub enum Language {
+    #[default]
+    English,
+    German,
+    Dutch,
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Language::English => write!(f, "English"),
+            Language::German => write!(f, "Deutsch"),
+            Language::Dutch => write!(f, "Nederlands"),
+        }
+    }
+}
+
+impl Language {
+    /// Translates a label or value written in English. Anything from ` (`
+    /// on is a symbol or unit and kept as it is, text without a translation
+    /// is left in English.
+    pub fn translate(&self, text: &str) -> String {
+        let (name, rest) = text.find(" (").map_or((text, ""), |i| text.split_at(i));
+
+        let translation = TRANSLATIONS
+            .iter()
+            .find(|(english, _, _)| *english == name)
+            .map(|(_, german, dutch)| match self {
+                Language::English => name,
+                Language::German => german,
+                Language::Dutch => dutch,
+            });
+
+        match translation {
+            Some(translation) => format!("{translation}{rest}"),
+            None => text.to_owned(),
+        }
+    }
+}
+
+/// English, German and Dutch text of everything a report prints by itself.
+const TRANSLATIONS: &[(&str, &str, &str)] = &[
+    // Title and page furniture.
+    ("Tension test report", "Prüfbericht Zugversuch", "Testrapport trekproef"),
+    ("Compression test report", "Prüfbericht Druckversuch", "Testrapport drukproef"),
+    ("3-point bend test report", "Prüfbericht Biegeversuch", "Testrapport buigproef"),
+    ("Tension series report", "Prüfbericht Zugversuchsreihe", "Testrapport trekproefserie"),
+    ("Compression series report", "Prüfbericht Druckversuchsreihe", "Testrapport drukproefserie"),
+    ("3-point bend series report", "Prüfbericht Biegeversuchsreihe", "Testrapport buigproefserie"),
+    ("Page", "Seite", "Pagina"),
+    ("of", "von", "van"),
+    // Section headings.
+    ("Test", "Prüfung", "Test"),
+    ("Specimen", "Probe", "Proefstuk"),
+    ("Results", "Ergebnisse", "Resultaten"),
+    ("Calibration", "Kalibrierung", "Kalibratie"),
+    ("Sign-off", "Freigabe", "Goedkeuring"),
+    ("Series", "Prüfreihe", "Serie"),
+    ("Specimens", "Proben", "Proefstukken"),
+    // Test information.
+    ("Date", "Datum", "Datum"),
+    ("Machine", "Maschine", "Machine"),
+    ("Test mode", "Prüfart", "Testsoort"),
+    ("Tension", "Zug", "Trek"),
+    ("Compression", "Druck", "Druk"),
+    ("3-point bend", "3-Punkt-Biegung", "3-puntsbuiging"),
+    ("Procedure", "Ablauf", "Procedure"),
+    ("Single pull", "Einzelner Zug", "Enkele trekproef"),
+    ("Test program", "Prüfprogramm", "Testprogramma"),
+    ("Cyclic", "Zyklisch", "Cyclisch"),
+    ("Stress relaxation", "Spannungsrelaxation", "Spanningsrelaxatie"),
+    ("Creep", "Kriechen", "Kruip"),
+    ("Stopped", "Abgebrochen", "Gestopt"),
+    ("Cancelled by the operator", "Vom Prüfer abgebrochen", "Afgebroken door de operator"),
+    ("Operator", "Prüfer", "Operator"),
+    ("Customer", "Kunde", "Klant"),
+    ("Material", "Werkstoff", "Materiaal"),
+    ("Batch", "Charge", "Batch"),
+    ("Specimen ID", "Proben-ID", "Proefstuk-ID"),
+    ("Temperature", "Temperatur", "Temperatuur"),
+    ("Humidity", "Luftfeuchte", "Luchtvochtigheid"),
+    ("Notes", "Bemerkungen", "Opmerkingen"),
+    ("Speed", "Geschwindigkeit", "Snelheid"),
+    ("Speed after switch", "Geschwindigkeit nach Umschaltung", "Snelheid na omschakeling"),
+    // Specimen and calibration.
+    ("Support span", "Auflagerabstand", "Oplegafstand"),
+    ("Width", "Breite", "Breedte"),
+    ("Thickness", "Dicke", "Dikte"),
+    ("Specimen area", "Probenquerschnitt", "Doorsnede proefstuk"),
+    ("Specimen height", "Probenhöhe", "Hoogte proefstuk"),
+    ("Gauge length", "Messlänge", "Meetlengte"),
+    ("Preload", "Vorkraft", "Voorbelasting"),
+    ("Compliance correction", "Nachgiebigkeitskorrektur", "Compliantiecorrectie"),
+    ("None", "Keine", "Geen"),
+    // Results.
+    ("Max force", "Höchstkraft", "Maximale kracht"),
+    ("Tensile strength", "Zugfestigkeit", "Treksterkte"),
+    ("Extension at Fm", "Verlängerung bei Fm", "Verlenging bij Fm"),
+    ("Elongation at Fm", "Dehnung bei Fm", "Rek bij Fm"),
+    ("Extension at break", "Verlängerung bei Bruch", "Verlenging bij breuk"),
+    ("Elongation at break", "Bruchdehnung", "Breukrek"),
+    ("Stress at break", "Spannung bei Bruch", "Spanning bij breuk"),
+    ("Energy to break", "Brucharbeit", "Breukenergie"),
+    ("Modulus", "Elastizitätsmodul", "Elasticiteitsmodulus"),
+    ("Modulus fit R²", "Modul-Fit R²", "Modulusfit R²"),
+    ("Yield strength", "Dehngrenze", "Rekgrens"),
+    ("Upper yield", "Obere Streckgrenze", "Bovenste vloeigrens"),
+    ("Lower yield", "Untere Streckgrenze", "Onderste vloeigrens"),
+    ("Strain hardening", "Verfestigungsexponent", "Verstevigingsexponent"),
+    ("Strength coefficient", "Festigkeitskoeffizient", "Sterktecoëfficiënt"),
+    ("Hardening fit R²", "Verfestigungs-Fit R²", "Verstevigingsfit R²"),
+    ("Compressive strength", "Druckfestigkeit", "Druksterkte"),
+    ("Compression at Fm", "Stauchung bei Fm", "Indrukking bij Fm"),
+    ("Strain at Fm", "Dehnung bei Fm", "Rek bij Fm"),
+    ("Energy absorbed", "Absorbierte Energie", "Geabsorbeerde energie"),
+    ("Compressive modulus", "Druckmodul", "Drukmodulus"),
+    ("Compressive yield", "Quetschgrenze", "Drukvloeigrens"),
+    ("Flexural strength", "Biegefestigkeit", "Buigsterkte"),
+    ("Deflection at Fm", "Durchbiegung bei Fm", "Doorbuiging bij Fm"),
+    ("Flexural strain at Fm", "Biegedehnung bei Fm", "Buigrek bij Fm"),
+    ("Deflection at break", "Durchbiegung bei Bruch", "Doorbuiging bij breuk"),
+    ("Flexural stress at break", "Biegespannung bei Bruch", "Buigspanning bij breuk"),
+    ("Flexural modulus", "Biegemodul", "Buigmodulus"),
+    // Series.
+    ("Excluded", "Ausgeschlossen", "Uitgesloten"),
+    ("Valid", "Gültig", "Geldig"),
+    ("Status", "Status", "Status"),
+    ("Reason", "Grund", "Reden"),
+    ("Result", "Ergebnis", "Resultaat"),
+    ("Mean", "Mittelwert", "Gemiddelde"),
+    ("SD", "s", "s"),
+    ("CV", "Vk", "VC"),
+    ("Min", "Min", "Min"),
+    ("Max", "Max", "Max"),
+    // Acceptance.
+    ("Acceptance", "Abnahme", "Acceptatie"),
+    ("Verdict", "Urteil", "Oordeel"),
+    ("Passed", "Bestanden", "Goedgekeurd"),
+    ("Failed", "Nicht bestanden", "Afgekeurd"),
+    ("Tolerance band", "Toleranzband", "Tolerantieband"),
+    // Curve.
+    ("Strain", "Dehnung", "Rek"),
+    ("Stress", "Spannung", "Spanning"),
+    ("Extension", "Verlängerung", "Verlenging"),
+    ("Force", "Kraft", "Kracht"),
+    ("Break", "Bruch", "Breuk"),
+    // Sign-off.
+    ("Tested by", "Geprüft von", "Getest door"),
+    ("Approved by", "Freigegeben von", "Goedgekeurd door"),
+    ("Signature", "Unterschrift", "Handtekening"),
+];
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+#[serde(default)]
+pub struct ReportTemplate {
+    pub name: String,
+    /// Sections in the order they are printed, with whether they are.
+    pub sections: Vec<(Section, bool)>,
+    /// Results left out of the report, by their name without symbol.
+    pub hidden_results: Vec<String>,
+    /// Printed at the top of every page.
+    pub header: String,
+    /// Printed at the bottom of every page.
+    pub footer: String,
+    pub language: Language,
+    /// Units of the report, `None` uses the units of the user preferences.
+    pub units: Option<Units>,
+}
+
+impl Default for ReportTemplate {
+    fn default() -> Self {
+        Self {
+            name: "Standard".to_owned(),
+            sections: [
+                Section::Test,
+                Section::Specimen,
+                Section::Chart,
+                Section::Results,
+                Section::Calibration,
+                Section::Signature,
+            ]
+            .into_iter()
+            .map(|section| (section, true))
+            .collect(),
+            hidden_results: Vec::new(),
+            header: String::new(),
+            footer: String::new(),
+            language: Language::English,
+            units: None,
+        }
+    }
+}
+
+impl ReportTemplate {
+    /// The sections that are printed, in order.
+    pub fn sections(&self) -> impl Iterator<Item = Section> + '_ {
+        self.sections.iter().filter(|(_, shown)| *shown).map(|(section, _)| *section)
+    }
+
+    pub fn shows_result(&self, name: &str) -> bool {
+        !self.hidden_results.iter().any(|hidden| hidden == result_key(name))
+    }
+
+    pub fn set_result_shown(&mut self, name: &str, shown: bool) {
+        let key = result_key(name);
+        self.hidden_results.retain(|hidden| hidden != key);
+        if !shown {
+            self.hidden_results.push(key.to_owned());
+        }
+    }
+}
+
+/// A result name without its symbol, which may depend on settings, like
+/// the offset in `Yield strength (Rp0.2)`.
+pub fn result_key(name: &str) -> &str {
+    name.split(" (").next().unwrap_or(name)
+}
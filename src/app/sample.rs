@@ -0,0 +1,69 @@
+/// A single reading recorded during a test, exactly as it came from the machine.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone, Copy)]
+pub struct Sample {
+    /// Seconds since the start of the test.
+    pub time: f64,
+    /// Crosshead position in mm.
+    pub position: f64,
+    /// Crosshead travel since the start of the test in mm.
+    pub extension: f64,
+    /// Force in N, zeroed at the start of the test.
+    pub force: f64,
+    /// Index of the test program stage the sample was taken in.
+    #[serde(default)]
+    pub stage: usize,
+}
+
+/// The `[extension, force]` curve of a test.
+pub fn force_extension(samples: &[Sample]) -> Vec<[f64; 2]> {
+    samples.iter().map(|s| [s.extension, s.force]).collect()
+}
+
+/// Indices of the samples that start a new test program stage.
+pub fn stage_boundaries(samples: &[Sample]) -> Vec<usize> {
+    (1..samples.len())
+        .filter(|&i| samples[i].stage != samples[i - 1].stage)
+        .collect()
+}
+
+/// Drops every other sample to bound memory on long tests, keeping the first
+/// sample of each stage and the last sample.
+pub fn decimate(samples: &mut Vec<Sample>) {
+    let last = samples.len().saturating_sub(1);
+    let mut previous_stage = None;
+    let mut i = 0;
+
+    samples.retain(|sample| {
+        let keep = i % 2 == 0 || i == last || previous_stage != Some(sample.stage);
+        previous_stage = Some(sample.stage);
+        i += 1;
+        keep
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decimate_keeps_stage_starts_and_the_last_sample() {
+        let mut samples: Vec<Sample> = [0, 0, 0, 1, 1, 1, 1, 2, 2, 2]
+            .into_iter()
+            .enumerate()
+            .map(|(i, stage)| Sample { time: i as f64, stage, ..Default::default() })
+            .collect();
+
+        decimate(&mut samples);
+
+        assert_eq!(samples.iter().map(|s| s.time).collect::<Vec<_>>(), [0.0, 2.0, 3.0, 4.0, 6.0, 7.0, 8.0, 9.0]);
+        assert_eq!(stage_boundaries(&samples), [2, 5]);
+    }
+
+    #[test]
+    fn decimate_an_empty_test() {
+        let mut samples = Vec::new();
+        decimate(&mut samples);
+
+        assert!(samples.is_empty());
+    }
+}
@@ -1,15 +1,32 @@
 use std::{io::BufRead, time::Duration};
 use anyhow::ensure;
 use serialport::{self, Error, SerialPortInfo};
-use log::{info, debug, error};
+use log::error;
+
+/// Reported positions are in 0.01mm.
+pub const POSITION_SCALE: f64 = 100.0;
+/// Reported forces are in 0.1N.
+pub const FORCE_SCALE: f64 = 10.0;
 
 #[derive(Debug, Default, Copy, Clone)]
 pub struct Values {
+  /// Counts the force readings received, so a new reading can be told apart from a repeated one.
   pub sample : u32,
   pub position : f32,
   pub tensile : i32,
 }
 
+impl Values {
+  pub fn position_mm(&self) -> f64 {
+    self.position as f64 / POSITION_SCALE
+  }
+
+  /// Force in N.
+  pub fn force(&self) -> f64 {
+    self.tensile as f64 / FORCE_SCALE
+  }
+}
+
 pub struct SerialDriver {
   values : Values,
   acknowledge_pending : bool,
@@ -85,9 +102,27 @@ impl SerialDriver {
 
     let s = speed as f32;
     let msg = format!("M700 S{s}\r\n" );
-    let res = self.send_message(&msg);
+    self.send_message(&msg)
+  }
 
-    res
+  /// Moves to an absolute position, in the units of `Values::position`, at `speed` mm/s
+  /// without waiting for the move to finish.
+  pub fn move_to(&mut self, position: f32, speed: f64) -> anyhow::Result<()> {
+    ensure!(self.serial_interface.is_some(), "No serial interface initialized");
+    ensure!(!self.acknowledge_pending, "Move in progress");
+    ensure!(self.is_homed, "Tensile tester not homed, please home first");
+
+    let pos = f32::max(position, 0.0);
+    let feed_rate = (speed * 60.0) as f32;
+    let msg = format!("G1 X{pos} F{feed_rate}\r\n");
+    self.send_message(&msg)
+  }
+
+  /// Stops any motion immediately.
+  pub fn stop(&mut self) -> anyhow::Result<()> {
+    ensure!(self.serial_interface.is_some(), "No serial interface initialized");
+
+    self.send_message("M410\r\n")
   }
 
   pub fn cancel_test(&mut self) {
@@ -105,7 +140,7 @@ impl SerialDriver {
           let res = reader.read_line(&mut line);
     
           match res {
-            Ok(n) => {
+            Ok(_) => {
               // debug!("RECV:{n}>{}", &line);
               let mut line_parts = line.split_whitespace();
     
@@ -125,6 +160,7 @@ impl SerialDriver {
                   if tag.contains("T:") {
                     let val = &tag[2..];
                     self.values.tensile = val.parse().unwrap();
+                    self.values.sample = self.values.sample.wrapping_add(1);
                     break;
                   }
                 }
@@ -150,7 +186,7 @@ impl SerialDriver {
 
     match self.serial_interface.as_deref_mut() {
       Some(s) => {
-        let bytes_written = s.write(bts)?;
+        s.write_all(bts)?;
          s.flush()?;
          Ok(())
       },
@@ -0,0 +1,208 @@
+//! Test series: specimens of one material tested with the same parameters,
+//! and statistics of their results.
+
+use chrono::{DateTime, Local};
+
+use super::machine_profile::Compliance;
+use super::report::result_key;
+use super::units::{Quantity, Units};
+use super::TestParamters;
+
+/// A result of a test in its internal unit, strains in %.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+pub struct ResultValue {
+    pub name: String,
+    pub quantity: Quantity,
+    /// Decimals the result is shown with.
+    pub decimals: usize,
+    pub value: Option<f64>,
+}
+
+impl ResultValue {
+    pub fn new(name: impl Into<String>, quantity: Quantity, decimals: usize, value: Option<f64>) -> Self {
+        Self { name: name.into(), quantity, decimals, value }
+    }
+
+    /// Formats a value of this result in `units`, with its symbol.
+    pub fn format(&self, value: f64, units: &Units) -> String {
+        units.quantity(self.quantity).format(value, self.decimals)
+    }
+}
+
+/// One specimen of a series.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct SeriesTest {
+    pub date: Option<DateTime<Local>>,
+    pub specimen_id: String,
+    /// Results as they were analysed when the test was added.
+    pub results: Vec<ResultValue>,
+    /// Why the specimen is left out of the statistics, `None` when it counts.
+    pub excluded: Option<String>,
+    /// The test in the history, if it was stored there.
+    pub history_id: Option<u64>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct Series {
+    pub name: String,
+    /// Every test of the series is run with these, apart from the specimen
+    /// geometry which is measured per specimen.
+    pub parameters: TestParamters,
+    /// The machine the series was started on.
+    pub machine: String,
+    /// The compliance correction applied when the series was started.
+    pub compliance: Option<Compliance>,
+    pub tests: Vec<SeriesTest>,
+}
+
+impl Series {
+    pub fn excluded_count(&self) -> usize {
+        self.tests.iter().filter(|test| test.excluded.is_some()).count()
+    }
+
+    /// Statistics of every result over the specimens that aren't excluded,
+    /// in the order the results are listed in. Results are told apart by
+    /// their name without the symbol, which settings like the yield offset
+    /// change.
+    pub fn statistics(&self) -> Vec<(ResultValue, Option<Statistics>)> {
+        let mut results: Vec<ResultValue> = Vec::new();
+        for result in self.tests.iter().flat_map(|test| &test.results) {
+            if !results.iter().any(|r| result_key(&r.name) == result_key(&result.name)) {
+                results.push(ResultValue { value: None, ..result.clone() });
+            }
+        }
+
+        results
+            .into_iter()
+            .map(|result| {
+                let values: Vec<f64> = self.tests.iter()
+                    .filter(|test| test.excluded.is_none())
+                    .filter_map(|test| test.results.iter().find(|r| result_key(&r.name) == result_key(&result.name))?.value)
+                    .collect();
+                (result, Statistics::new(&values))
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Statistics {
+    pub count: usize,
+    pub mean: f64,
+    /// Sample standard deviation, zero for a single value.
+    pub std_dev: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Statistics {
+    pub fn new(values: &[f64]) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let std_dev = if values.len() > 1 {
+            (values.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt()
+        } else {
+            0.0
+        };
+
+        Some(Self {
+            count: values.len(),
+            mean,
+            std_dev,
+            min: values.iter().copied().fold(f64::INFINITY, f64::min),
+            max: values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        })
+    }
+
+    /// Standard deviation as a % of the mean, `None` for a zero mean.
+    pub fn coefficient_of_variation(&self) -> Option<f64> {
+        (self.mean != 0.0).then(|| self.std_dev / self.mean.abs() * 100.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn statistics_of_values() {
+        let statistics = Statistics::new(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]).unwrap();
+
+        assert_eq!(statistics.count, 8);
+        assert_eq!(statistics.mean, 5.0);
+        assert!((statistics.std_dev - (32.0_f64 / 7.0).sqrt()).abs() < 1e-12);
+        assert_eq!(statistics.min, 2.0);
+        assert_eq!(statistics.max, 9.0);
+        assert!((statistics.coefficient_of_variation().unwrap() - statistics.std_dev / 5.0 * 100.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn statistics_of_a_single_value() {
+        let statistics = Statistics::new(&[-3.0]).unwrap();
+
+        assert_eq!(statistics.std_dev, 0.0);
+        assert_eq!(statistics.coefficient_of_variation(), Some(0.0));
+    }
+
+    #[test]
+    fn statistics_need_values() {
+        assert!(Statistics::new(&[]).is_none());
+    }
+
+    #[test]
+    fn coefficient_of_variation_needs_a_mean() {
+        let statistics = Statistics::new(&[-1.0, 1.0]).unwrap();
+
+        assert!(statistics.coefficient_of_variation().is_none());
+    }
+
+    #[test]
+    fn coefficient_of_variation_of_negative_means_is_positive() {
+        let statistics = Statistics::new(&[-1.0, -3.0]).unwrap();
+
+        assert!(statistics.coefficient_of_variation().unwrap() > 0.0);
+    }
+
+    #[test]
+    fn excluded_tests_are_left_out() {
+        let test = |value, excluded: Option<&str>| SeriesTest {
+            results: vec![ResultValue::new("Max force (Fm)", Quantity::Force, 1, Some(value))],
+            excluded: excluded.map(str::to_owned),
+            ..Default::default()
+        };
+        let series = Series {
+            tests: vec![test(100.0, None), test(1000.0, Some("Broke in the grips")), test(110.0, None)],
+            ..Default::default()
+        };
+
+        let statistics = series.statistics();
+
+        assert_eq!(series.excluded_count(), 1);
+        assert_eq!(statistics.len(), 1);
+        let values = statistics[0].1.unwrap();
+        assert_eq!((values.count, values.mean), (2, 105.0));
+    }
+
+    #[test]
+    fn results_are_grouped_without_their_symbol() {
+        let test = |name: &str, value| SeriesTest {
+            results: vec![ResultValue::new(name, Quantity::Stress, 1, Some(value))],
+            ..Default::default()
+        };
+        let series = Series {
+            tests: vec![test("Yield strength (Rp0.2)", 200.0), test("Yield strength (Rp0.5)", 220.0)],
+            ..Default::default()
+        };
+
+        let statistics = series.statistics();
+
+        assert_eq!(statistics.len(), 1);
+        assert_eq!(statistics[0].1.unwrap().count, 2);
+    }
+}
@@ -0,0 +1,124 @@
+//! Test speeds given as crosshead speed, strain rate or stress rate, and
+//! their conversion into the crosshead speed the machine runs at.
+
+use std::fmt::{self, Formatter};
+
+use strum_macros::EnumIter;
+
+use super::analysis::Specimen;
+use super::test_program::LimitKind;
+
+/// Slowest crosshead speed the machine runs at, in mm/s.
+pub const MIN_CROSSHEAD_SPEED: f64 = 0.01;
+/// Fastest crosshead speed the machine runs at, in mm/s.
+pub const MAX_CROSSHEAD_SPEED: f64 = 10.0;
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone, Copy, PartialEq, EnumIter)]
+pub enum SpeedUnit {
+    /// mm/s
+    #[default]
+    Crosshead,
+    /// 1/s
+    StrainRate,
+    /// MPa/s
+    StressRate,
+}
+
+impl fmt::Display for SpeedUnit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SpeedUnit::Crosshead => write!(f, "mm/s"),
+            SpeedUnit::StrainRate => write!(f, "1/s"),
+            SpeedUnit::StressRate => write!(f, "MPa/s"),
+        }
+    }
+}
+
+impl SpeedUnit {
+    /// Converts a speed in this unit into mm/s, `None` when the specimen
+    /// geometry or the modulus needed for it are missing.
+    ///
+    /// Stress rates apply to the elastic range, where they are a strain rate
+    /// of `stress rate / modulus`. Compliance of the frame is not accounted for.
+    pub fn crosshead_speed(&self, value: f64, specimen: &Specimen, modulus: f64) -> Option<f64> {
+        match self {
+            SpeedUnit::Crosshead => Some(value),
+            SpeedUnit::StrainRate => (specimen.gauge_length > 0.0).then_some(value * specimen.gauge_length),
+            SpeedUnit::StressRate => {
+                (specimen.gauge_length > 0.0 && modulus > 0.0).then_some(value / modulus * specimen.gauge_length)
+            },
+        }
+    }
+
+    /// Step size for dragging a value in this unit.
+    pub fn drag_speed(&self) -> f64 {
+        match self {
+            SpeedUnit::Crosshead => 0.01,
+            SpeedUnit::StrainRate => 0.00001,
+            SpeedUnit::StressRate => 0.1,
+        }
+    }
+}
+
+/// Changes the test speed once the specimen reaches a limit, e.g. from a
+/// stress rate in the elastic range to a strain rate after yield.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy)]
+#[serde(default)]
+pub struct RateSwitch {
+    pub enabled: bool,
+    pub limit: LimitKind,
+    /// Value of `limit` at which the speed changes.
+    pub at: f64,
+    /// Speed after the switch, in `unit`.
+    pub speed: f64,
+    pub unit: SpeedUnit,
+}
+
+impl Default for RateSwitch {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            limit: LimitKind::Strain,
+            at: 0.5,
+            speed: 0.00025,
+            unit: SpeedUnit::StrainRate,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPECIMEN: Specimen = Specimen { area: 20.0, gauge_length: 50.0 };
+
+    #[test]
+    fn crosshead_speed_is_taken_as_it_is() {
+        assert_eq!(SpeedUnit::Crosshead.crosshead_speed(0.5, &SPECIMEN, 0.0), Some(0.5));
+    }
+
+    #[test]
+    fn strain_rate_scales_with_the_gauge_length() {
+        // 0.00025 1/s over 50 mm.
+        let speed = SpeedUnit::StrainRate.crosshead_speed(0.00025, &SPECIMEN, 0.0).unwrap();
+
+        assert!((speed - 0.0125).abs() < 1e-12);
+    }
+
+    #[test]
+    fn stress_rate_is_a_strain_rate_through_the_modulus() {
+        // 20 MPa/s on 200 GPa is 0.0001 1/s, over 50 mm.
+        let speed = SpeedUnit::StressRate.crosshead_speed(20.0, &SPECIMEN, 200_000.0).unwrap();
+
+        assert!((speed - 0.005).abs() < 1e-12);
+    }
+
+    #[test]
+    fn rates_need_geometry_and_modulus() {
+        let no_geometry = Specimen { area: 0.0, gauge_length: 0.0 };
+
+        assert_eq!(SpeedUnit::StrainRate.crosshead_speed(0.00025, &no_geometry, 200_000.0), None);
+        assert_eq!(SpeedUnit::StressRate.crosshead_speed(20.0, &no_geometry, 200_000.0), None);
+        assert_eq!(SpeedUnit::StressRate.crosshead_speed(20.0, &SPECIMEN, 0.0), None);
+    }
+}
@@ -0,0 +1,194 @@
+//! The native test file: a JSON document holding everything needed to view
+//! and re-analyse a test without the machine it was run on.
+//!
+//! Every file starts with `format` and `version`. Files written by older
+//! versions are upgraded step by step through `MIGRATIONS` before they are
+//! read, fields a file doesn't have get their defaults and fields this
+//! version doesn't know are ignored.
+
+use std::{fs, io::BufWriter, path::{Path, PathBuf}};
+
+use anyhow::{bail, Context};
+use serde_json::{Map, Value};
+
+use super::acceptance::Verdict;
+use super::analysis::{Analysis, AnalysisSettings};
+use super::filter::FilterSettings;
+use super::machine_profile::MachineProfile;
+use super::sample::Sample;
+use super::test_metadata::TestMetadata;
+
+/// Identifies a file as a test file, whatever its extension.
+const FORMAT: &str = "industrio-tensile-test";
+
+/// Version files are written in, bump it and add a migration whenever the
+/// layout changes in a way defaults can't cover.
+const VERSION: u64 = 1;
+
+pub const EXTENSION: &str = "itest";
+
+/// `MIGRATIONS[i]` upgrades a version `i + 1` file to version `i + 2`.
+type Migration = fn(&mut Map<String, Value>) -> anyhow::Result<()>;
+const MIGRATIONS: &[Migration] = &[];
+
+/// A test as it is saved to disk.
+#[derive(serde::Deserialize, serde::Serialize, Default)]
+#[serde(default)]
+pub struct TestFile {
+    pub metadata: TestMetadata,
+    /// The machine the test was run on, as it was at the time.
+    pub machine_profile: MachineProfile,
+    pub analysis_settings: AnalysisSettings,
+    pub filter_settings: FilterSettings,
+    /// Raw samples, never filtered or corrected.
+    pub samples: Vec<Sample>,
+    /// Results as they were computed when the file was saved.
+    pub analysis: Analysis,
+    /// Outcome of the acceptance checks after the test, if it was checked.
+    pub verdict: Option<Verdict>,
+}
+
+#[derive(serde::Serialize)]
+struct Header<'a> {
+    format: &'a str,
+    version: u64,
+    #[serde(flatten)]
+    test: &'a TestFile,
+}
+
+impl TestFile {
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let file = fs::File::create(path)?;
+        let header = Header { format: FORMAT, version: VERSION, test: self };
+        serde_json::to_writer(BufWriter::new(file), &header)?;
+        Ok(())
+    }
+
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        let Value::Object(mut fields) = serde_json::from_str(&text).context("Not a test file")? else {
+            bail!("Not a test file");
+        };
+
+        if fields.remove("format").as_ref().and_then(Value::as_str) != Some(FORMAT) {
+            bail!("Not a test file");
+        }
+        let version = fields.remove("version").as_ref().and_then(Value::as_u64).context("Test file has no version")?;
+        upgrade(&mut fields, version, MIGRATIONS)?;
+
+        Ok(serde_json::from_value(Value::Object(fields))?)
+    }
+}
+
+/// Runs the `migrations` a version `version` file still needs.
+fn upgrade(fields: &mut Map<String, Value>, version: u64, migrations: &[Migration]) -> anyhow::Result<()> {
+    if version == 0 || version > migrations.len() as u64 + 1 {
+        bail!("Test file version {version} needs a newer version of this program");
+    }
+
+    for migration in &migrations[version as usize - 1..] {
+        migration(fields)?;
+    }
+    Ok(())
+}
+
+/// Test files in `folder`, newest first.
+pub fn list(folder: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(folder) else {
+        return Vec::new();
+    };
+
+    let mut files: Vec<(PathBuf, Option<std::time::SystemTime>)> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == EXTENSION))
+        .map(|path| {
+            let modified = fs::metadata(&path).and_then(|metadata| metadata.modified()).ok();
+            (path, modified)
+        })
+        .collect();
+
+    files.sort_by(|a, b| b.1.cmp(&a.1));
+    files.into_iter().map(|(path, _)| path).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn every_version_has_a_migration() {
+        assert_eq!(MIGRATIONS.len() as u64 + 1, VERSION);
+    }
+
+    #[test]
+    fn migrations_run_in_order_from_the_file_version() {
+        let migrations: &[Migration] = &[
+            |fields| {
+                let name = fields.remove("name").unwrap_or_default();
+                fields.insert("title".to_owned(), name);
+                Ok(())
+            },
+            |fields| {
+                let title = fields["title"].as_str().unwrap_or_default().to_uppercase();
+                fields.insert("title".to_owned(), json!(title));
+                Ok(())
+            },
+        ];
+
+        let mut version_1 = json!({"name": "bar"}).as_object().unwrap().clone();
+        upgrade(&mut version_1, 1, migrations).unwrap();
+        assert_eq!(Value::Object(version_1), json!({"title": "BAR"}));
+
+        let mut version_2 = json!({"title": "bar"}).as_object().unwrap().clone();
+        upgrade(&mut version_2, 2, migrations).unwrap();
+        assert_eq!(Value::Object(version_2), json!({"title": "BAR"}));
+
+        let mut version_3 = json!({"title": "bar"}).as_object().unwrap().clone();
+        upgrade(&mut version_3, 3, migrations).unwrap();
+        assert_eq!(Value::Object(version_3), json!({"title": "bar"}));
+    }
+
+    #[test]
+    fn unknown_versions_are_rejected() {
+        let mut fields = Map::new();
+
+        assert!(upgrade(&mut fields, 0, MIGRATIONS).is_err());
+        assert!(upgrade(&mut fields, VERSION + 1, MIGRATIONS).is_err());
+    }
+
+    #[test]
+    fn files_round_trip_and_tolerate_unknown_fields() {
+        let path = std::env::temp_dir().join(format!("tensile_test_file_{}.{EXTENSION}", std::process::id()));
+        let file = TestFile {
+            samples: vec![Sample { time: 1.0, force: 2.0, extension: 3.0, ..Default::default() }],
+            ..Default::default()
+        };
+        file.save(&path).unwrap();
+
+        let mut fields: Map<String, Value> = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(fields["format"], json!(FORMAT));
+        assert_eq!(fields["version"], json!(VERSION));
+        fields.insert("added_later".to_owned(), json!(true));
+        fields.remove("verdict");
+        fs::write(&path, Value::Object(fields).to_string()).unwrap();
+
+        let opened = TestFile::open(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(opened.samples.len(), 1);
+        assert_eq!(opened.samples[0].force, 2.0);
+        assert!(opened.verdict.is_none());
+    }
+
+    #[test]
+    fn other_json_is_not_a_test_file() {
+        let path = std::env::temp_dir().join(format!("tensile_not_a_test_{}.{EXTENSION}", std::process::id()));
+        fs::write(&path, json!({"format": "something-else", "version": 1}).to_string()).unwrap();
+
+        let result = TestFile::open(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,77 @@
+use chrono::{DateTime, Local};
+
+use super::machine_profile::Compliance;
+use super::test_program::{TestMode, TestProcedure, TestProgram};
+use super::TestParamters;
+
+/// Information recorded about a test run, next to its samples.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct TestMetadata {
+    /// Force in N the specimen was preloaded to before extension was zeroed.
+    pub preload_force: Option<f64>,
+    /// Extension and force were recorded positive in the loading direction of this mode.
+    pub mode: TestMode,
+    pub procedure: TestProcedure,
+    /// The stages that were run, `Sample::stage` indexes into these.
+    pub program: Option<TestProgram>,
+    /// When the test started.
+    pub date: Option<DateTime<Local>>,
+    /// The settings the test was run with.
+    pub parameters: TestParamters,
+    /// Frame compliance subtracted from the extension, if any.
+    pub compliance: Option<Compliance>,
+    /// Entered in the test information form before the test.
+    pub info: TestInfo,
+    /// The operator stopped the test before it ended by itself.
+    pub cancelled: bool,
+}
+
+/// What was tested, by whom and under which conditions, as entered by the operator.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct TestInfo {
+    pub operator: String,
+    pub customer: String,
+    pub material: String,
+    /// Batch or lot number.
+    pub batch: String,
+    pub specimen_id: String,
+    /// Ambient temperature in °C.
+    pub temperature: Option<f64>,
+    /// Relative humidity in %.
+    pub humidity: Option<f64>,
+    pub notes: String,
+    /// Name–value pairs for anything the fields above don't cover.
+    pub custom_fields: Vec<(String, String)>,
+}
+
+impl TestInfo {
+    /// Name–value rows of the fields that were filled in.
+    pub fn rows(&self) -> Vec<(String, String)> {
+        let mut rows: Vec<(String, String)> = [
+            ("Operator", &self.operator),
+            ("Customer", &self.customer),
+            ("Material", &self.material),
+            ("Batch", &self.batch),
+            ("Specimen ID", &self.specimen_id),
+        ]
+        .into_iter()
+        .filter(|(_, value)| !value.is_empty())
+        .map(|(name, value)| (name.to_owned(), value.clone()))
+        .collect();
+
+        if let Some(temperature) = self.temperature {
+            rows.push(("Temperature".to_owned(), format!("{temperature:.1}°C")));
+        }
+        if let Some(humidity) = self.humidity {
+            rows.push(("Humidity".to_owned(), format!("{humidity:.0}%")));
+        }
+        rows.extend(self.custom_fields.iter().filter(|(name, _)| !name.is_empty()).cloned());
+        if !self.notes.is_empty() {
+            rows.push(("Notes".to_owned(), self.notes.clone()));
+        }
+
+        rows
+    }
+}
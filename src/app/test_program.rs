@@ -0,0 +1,500 @@
+//! Test programs made of stages, executed from the host by sending moves to
+//! the machine and watching the measurements.
+
+use std::{fmt::{self, Formatter}, time::Instant};
+
+use anyhow::bail;
+use strum_macros::EnumIter;
+
+use super::serial_driver::{SerialDriver, POSITION_SCALE};
+use super::units::Units;
+
+/// Furthest a tension stage moves when it ends on force or strain and no
+/// maximum distance is set, in mm. Compression and bend tests can't start
+/// without a maximum distance.
+const MAX_STAGE_TRAVEL: f64 = 500.0;
+/// How close to its target a move has to be to count as arrived, in mm.
+const POSITION_TOLERANCE: f64 = 0.01;
+/// Force error, as a fraction of the target force, a force hold tolerates.
+const FORCE_TOLERANCE: f64 = 0.005;
+/// Part of the force error corrected per correction, to keep the loop stable.
+const FORCE_CORRECTION_GAIN: f64 = 0.5;
+/// Largest single correction move while holding force, in mm.
+const MAX_FORCE_CORRECTION: f64 = 0.1;
+/// Time between corrections while holding force, in s.
+const FORCE_CORRECTION_INTERVAL: f64 = 0.25;
+/// Most cycles a cyclic test runs, every cycle gets a row of results.
+pub const MAX_CYCLES: u32 = 10_000;
+
+/// Which way the specimen is loaded.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone, Copy, PartialEq, EnumIter)]
+pub enum TestMode {
+    #[default]
+    Tension,
+    Compression,
+    /// Three point bending, the crosshead pushes down on the middle of the specimen.
+    Flexure,
+}
+
+impl fmt::Display for TestMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TestMode::Tension => write!(f, "Tension"),
+            TestMode::Compression => write!(f, "Compression"),
+            TestMode::Flexure => write!(f, "3-point bend"),
+        }
+    }
+}
+
+impl TestMode {
+    /// Sign that turns crosshead travel and load cell readings into positive
+    /// extension and force while loading the specimen. Pulling moves the
+    /// crosshead towards lower positions and reads positive force.
+    pub fn direction(&self) -> f64 {
+        match self {
+            TestMode::Tension => 1.0,
+            TestMode::Compression | TestMode::Flexure => -1.0,
+        }
+    }
+}
+
+/// How the machine is driven during a test.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone, Copy, PartialEq, EnumIter)]
+pub enum TestProcedure {
+    /// A single pull at constant speed, run by the firmware.
+    #[default]
+    Pull,
+    /// A user defined sequence of stages.
+    Program,
+    /// Cycling between two limits.
+    Cyclic,
+    /// Move to an extension and hold it, logging the force.
+    Relaxation,
+    /// Load to a force and hold it, logging the extension.
+    Creep,
+}
+
+impl fmt::Display for TestProcedure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TestProcedure::Pull => write!(f, "Single pull"),
+            TestProcedure::Program => write!(f, "Test program"),
+            TestProcedure::Cyclic => write!(f, "Cyclic"),
+            TestProcedure::Relaxation => write!(f, "Stress relaxation"),
+            TestProcedure::Creep => write!(f, "Creep"),
+        }
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, EnumIter)]
+pub enum StageKind {
+    /// Pull at a constant speed until the limit is reached.
+    Move,
+    /// Stand still for a time.
+    Hold,
+    /// Keep the force at the limit value for a time by adjusting the position.
+    HoldForce,
+    /// Move back towards the start until the value drops to the limit.
+    Unload,
+}
+
+impl fmt::Display for StageKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            StageKind::Move => write!(f, "Move"),
+            StageKind::Hold => write!(f, "Hold"),
+            StageKind::HoldForce => write!(f, "Hold force"),
+            StageKind::Unload => write!(f, "Unload"),
+        }
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, EnumIter)]
+pub enum LimitKind {
+    Extension,
+    Force,
+    Strain,
+}
+
+impl fmt::Display for LimitKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitKind::Extension => write!(f, "Extension"),
+            LimitKind::Force => write!(f, "Force"),
+            LimitKind::Strain => write!(f, "Strain"),
+        }
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy)]
+#[serde(default)]
+pub struct Stage {
+    pub kind: StageKind,
+    /// Crosshead speed in mm/s for moves.
+    pub speed: f64,
+    pub limit: LimitKind,
+    /// Value of `limit` that ends a move, in the unit of the limit.
+    pub limit_value: f64,
+    /// Duration of a hold in s.
+    pub duration: f64,
+}
+
+impl Default for Stage {
+    fn default() -> Self {
+        Self {
+            kind: StageKind::Move,
+            speed: 1.0,
+            limit: LimitKind::Extension,
+            limit_value: 1.0,
+            duration: 10.0,
+        }
+    }
+}
+
+impl Stage {
+    /// What the stage does, in `units`.
+    pub fn describe(&self, units: &Units) -> String {
+        match self.kind {
+            StageKind::Hold => format!("Hold {}s", self.duration),
+            StageKind::HoldForce => format!("Hold {} for {}s", units.force().format(self.limit_value, 3), self.duration),
+            kind => format!("{kind} at {} to {} {}", units.speed().format(self.speed, 5), self.limit, units.limit(self.limit).format(self.limit_value, 3)),
+        }
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+#[serde(default)]
+pub struct TestProgram {
+    pub stages: Vec<Stage>,
+    /// How many times `stages` run one after the other, stage `i` of the
+    /// run is `stages[i % stages.len()]`. Long cyclic tests stay a couple
+    /// of stages instead of one per cycle.
+    pub repeat: u32,
+}
+
+impl Default for TestProgram {
+    fn default() -> Self {
+        Self {
+            stages: Vec::new(),
+            repeat: 1,
+        }
+    }
+}
+
+impl TestProgram {
+    /// Number of stages run, counting every repeat.
+    pub fn len(&self) -> usize {
+        self.stages.len().saturating_mul(self.repeat.max(1) as usize)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Stage `i` of the run.
+    pub fn stage(&self, i: usize) -> Option<&Stage> {
+        if i >= self.len() {
+            return None;
+        }
+
+        self.stages.get(i % self.stages.len())
+    }
+
+    /// A single constant speed move up to `max_travel` mm, `0` uses the
+    /// default limit. Runs single pulls the firmware cannot, it only pulls.
+    pub fn single_move(speed: f64, max_travel: f64) -> Self {
+        Self {
+            stages: vec![Stage {
+                kind: StageKind::Move,
+                speed,
+                limit: LimitKind::Extension,
+                limit_value: if max_travel > 0.0 { max_travel } else { MAX_STAGE_TRAVEL },
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    /// A move at `speed` until `limit` reaches `limit_value`, carrying on at
+    /// `then_speed` up to `max_travel` mm.
+    pub fn switched_move(speed: f64, limit: LimitKind, limit_value: f64, then_speed: f64, max_travel: f64) -> Self {
+        let mut program = Self::single_move(then_speed, max_travel);
+        program.stages.insert(0, Stage { kind: StageKind::Move, speed, limit, limit_value, ..Default::default() });
+        program
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy)]
+#[serde(default)]
+pub struct CyclicSettings {
+    /// What the cycle limits are in, extension or force.
+    pub limit: LimitKind,
+    pub upper: f64,
+    pub lower: f64,
+    pub cycles: u32,
+    /// Crosshead speed in mm/s.
+    pub speed: f64,
+    /// Drop in peak force compared to the first cycle, in %, that counts as failure.
+    pub failure_drop: f64,
+}
+
+impl Default for CyclicSettings {
+    fn default() -> Self {
+        Self {
+            limit: LimitKind::Extension,
+            upper: 1.0,
+            lower: 0.0,
+            cycles: 10,
+            speed: 1.0,
+            failure_drop: 20.0,
+        }
+    }
+}
+
+impl CyclicSettings {
+    /// Every cycle is a load and an unload stage, so stage `i` belongs to cycle `i / 2`.
+    pub fn program(&self) -> TestProgram {
+        let stage = |kind, limit_value| Stage {
+            kind,
+            speed: self.speed,
+            limit: self.limit,
+            limit_value,
+            ..Default::default()
+        };
+
+        TestProgram {
+            stages: vec![stage(StageKind::Move, self.upper), stage(StageKind::Unload, self.lower)],
+            repeat: self.cycles.clamp(1, MAX_CYCLES),
+        }
+    }
+}
+
+/// Settings for relaxation and creep tests: load to `target` and hold it.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy)]
+#[serde(default)]
+pub struct HoldSettings {
+    /// Extension in mm for relaxation, force in N for creep.
+    pub target: f64,
+    /// Crosshead speed while loading in mm/s.
+    pub speed: f64,
+    /// How long to hold, in hours.
+    pub hours: f64,
+}
+
+impl Default for HoldSettings {
+    fn default() -> Self {
+        Self {
+            target: 1.0,
+            speed: 0.1,
+            hours: 1.0,
+        }
+    }
+}
+
+impl HoldSettings {
+    pub fn relaxation_program(&self) -> TestProgram {
+        TestProgram {
+            stages: vec![
+                Stage { kind: StageKind::Move, speed: self.speed, limit: LimitKind::Extension, limit_value: self.target, ..Default::default() },
+                Stage { kind: StageKind::Hold, duration: self.hours * 3600.0, ..Default::default() },
+            ],
+            ..Default::default()
+        }
+    }
+
+    pub fn creep_program(&self) -> TestProgram {
+        TestProgram {
+            stages: vec![
+                Stage { kind: StageKind::Move, speed: self.speed, limit: LimitKind::Force, limit_value: self.target, ..Default::default() },
+                Stage { kind: StageKind::HoldForce, speed: self.speed, limit: LimitKind::Force, limit_value: self.target, duration: self.hours * 3600.0 },
+            ],
+            ..Default::default()
+        }
+    }
+}
+
+/// The state of the test the program reacts to.
+#[derive(Debug, Clone, Copy)]
+pub struct Measurement {
+    /// Position in the units of `Values::position`.
+    pub position: f32,
+    /// Extension in mm.
+    pub extension: f64,
+    /// Force in N.
+    pub force: f64,
+    /// Strain as a fraction, when the gauge length is known.
+    pub strain: Option<f64>,
+}
+
+impl Measurement {
+    fn value(&self, limit: LimitKind) -> anyhow::Result<f64> {
+        match limit {
+            LimitKind::Extension => Ok(self.extension),
+            LimitKind::Force => Ok(self.force),
+            LimitKind::Strain => match self.strain {
+                Some(strain) => Ok(strain * 100.0),
+                None => bail!("Strain limits need a gauge length"),
+            },
+        }
+    }
+}
+
+/// Runs a program one stage at a time.
+#[derive(Debug)]
+pub struct ProgramRunner {
+    program: TestProgram,
+    pub stage: usize,
+    mode: TestMode,
+    /// Position of the crosshead at zero extension.
+    start_position: f32,
+    /// Furthest a stage may move from the start, in mm.
+    max_travel: f64,
+    /// When the current stage started and where it is moving to.
+    current: Option<(Instant, f32)>,
+    /// Force per mm of extension, measured when a force hold starts.
+    stiffness: f64,
+    last_correction: Option<Instant>,
+}
+
+impl ProgramRunner {
+    /// `max_travel` limits moves that end on force or strain, `0` uses the default limit.
+    pub fn new(program: TestProgram, mode: TestMode, start_position: f32, max_travel: f64) -> Self {
+        Self {
+            program,
+            stage: 0,
+            mode,
+            start_position,
+            max_travel: if max_travel > 0.0 { max_travel } else { MAX_STAGE_TRAVEL },
+            current: None,
+            stiffness: 0.0,
+            last_correction: None,
+        }
+    }
+
+    /// Advances the program, returns `false` once every stage is done.
+    pub fn update(&mut self, driver: &mut SerialDriver, measurement: &Measurement) -> anyhow::Result<bool> {
+        let Some(stage) = self.program.stage(self.stage).copied() else {
+            return Ok(false);
+        };
+
+        let (started, target) = match self.current {
+            Some(current) => current,
+            None => {
+                let target = self.begin(&stage, driver, measurement)?;
+                self.current = Some((Instant::now(), target));
+                return Ok(true);
+            },
+        };
+
+        let arrived = (measurement.position - target).abs() as f64 <= POSITION_TOLERANCE * POSITION_SCALE;
+        let done = match stage.kind {
+            StageKind::Move => arrived || measurement.value(stage.limit)? >= stage.limit_value,
+            StageKind::Unload => arrived || measurement.value(stage.limit)? <= stage.limit_value,
+            StageKind::Hold => started.elapsed().as_secs_f64() >= stage.duration,
+            StageKind::HoldForce => {
+                self.correct_force(&stage, driver, measurement)?;
+                started.elapsed().as_secs_f64() >= stage.duration
+            },
+        };
+
+        if done {
+            if stage.kind != StageKind::Hold {
+                driver.stop()?;
+            }
+
+            self.stage += 1;
+            self.current = None;
+        }
+
+        Ok(self.stage < self.program.len())
+    }
+
+    /// Moves a little towards the target force, using the stiffness of the
+    /// specimen to estimate how far.
+    fn correct_force(&mut self, stage: &Stage, driver: &mut SerialDriver, measurement: &Measurement) -> anyhow::Result<()> {
+        if self.last_correction.is_some_and(|last| last.elapsed().as_secs_f64() < FORCE_CORRECTION_INTERVAL) {
+            return Ok(());
+        }
+
+        let error = stage.limit_value - measurement.force;
+        if error.abs() <= stage.limit_value.abs() * FORCE_TOLERANCE {
+            return Ok(());
+        }
+
+        let correction = (error / self.stiffness * FORCE_CORRECTION_GAIN).clamp(-MAX_FORCE_CORRECTION, MAX_FORCE_CORRECTION);
+        let target = measurement.position - (self.mode.direction() * correction * POSITION_SCALE) as f32;
+
+        driver.move_to(target, stage.speed)?;
+        self.last_correction = Some(Instant::now());
+        Ok(())
+    }
+
+    /// Sends the move for `stage` and returns where it is going.
+    fn begin(&mut self, stage: &Stage, driver: &mut SerialDriver, measurement: &Measurement) -> anyhow::Result<f32> {
+        let offset = |extension: f64| self.start_position - (self.mode.direction() * extension * POSITION_SCALE) as f32;
+
+        let target = match (stage.kind, stage.limit) {
+            (StageKind::Hold, _) => return Ok(measurement.position),
+            (StageKind::HoldForce, _) => {
+                if measurement.extension <= 0.0 || measurement.force <= 0.0 {
+                    bail!("Load the specimen before holding force");
+                }
+
+                self.stiffness = measurement.force / measurement.extension;
+                self.last_correction = None;
+                return Ok(measurement.position);
+            },
+            (StageKind::Move, LimitKind::Extension) => offset(stage.limit_value),
+            (StageKind::Move, _) => offset(self.max_travel),
+            (StageKind::Unload, LimitKind::Extension) => offset(stage.limit_value),
+            (StageKind::Unload, _) => self.start_position,
+        };
+
+        driver.move_to(target, stage.speed)?;
+        Ok(target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::units::{ForceUnit, LengthUnit};
+
+    #[test]
+    fn cyclic_program_repeats_its_stages() {
+        let settings = CyclicSettings { cycles: 1000, ..Default::default() };
+        let program = settings.program();
+
+        assert_eq!(program.stages.len(), 2);
+        assert_eq!(program.len(), 2000);
+        assert_eq!(program.stage(4).map(|s| s.kind), Some(StageKind::Move));
+        assert_eq!(program.stage(5).map(|s| s.kind), Some(StageKind::Unload));
+        assert!(program.stage(program.len()).is_none());
+    }
+
+    #[test]
+    fn cycles_are_capped() {
+        let settings = CyclicSettings { cycles: u32::MAX, ..Default::default() };
+
+        assert_eq!(settings.program().len(), 2 * MAX_CYCLES as usize);
+    }
+
+    #[test]
+    fn stages_are_described_in_the_units_picked() {
+        let stage = Stage { speed: 2.54, limit: LimitKind::Force, limit_value: 1000.0, ..Default::default() };
+        let hold = Stage { kind: StageKind::HoldForce, limit_value: 500.0, duration: 30.0, ..Default::default() };
+        let units = Units { force: ForceUnit::Kilonewton, length: LengthUnit::Inch, ..Default::default() };
+
+        assert_eq!(stage.describe(&units), "Move at 0.10000in/s to Force 1.000kN");
+        assert_eq!(hold.describe(&units), "Hold 0.500kN for 30s");
+        assert_eq!(stage.describe(&Units::default()), "Move at 2.54000mm/s to Force 1000.000N");
+    }
+
+    #[test]
+    fn program_without_repeat_runs_once() {
+        let program: TestProgram = serde_json::from_str(r#"{"stages": [{"kind": "Hold"}]}"#).unwrap();
+
+        assert_eq!(program.len(), 1);
+        assert!(program.stage(1).is_none());
+    }
+}
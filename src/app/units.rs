@@ -0,0 +1,274 @@
+//! Units values are shown and entered in. Everything is stored and
+//! calculated in N, mm, s and MPa, conversion only happens at the edges.
+
+use std::fmt::{self, Formatter};
+
+use strum_macros::EnumIter;
+
+use super::speed::SpeedUnit;
+use super::test_program::LimitKind;
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone, Copy, PartialEq, EnumIter)]
+pub enum ForceUnit {
+    #[default]
+    Newton,
+    Kilonewton,
+    PoundForce,
+    KilogramForce,
+}
+
+impl fmt::Display for ForceUnit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ForceUnit::Newton => write!(f, "N"),
+            ForceUnit::Kilonewton => write!(f, "kN"),
+            ForceUnit::PoundForce => write!(f, "lbf"),
+            ForceUnit::KilogramForce => write!(f, "kgf"),
+        }
+    }
+}
+
+impl ForceUnit {
+    /// N per unit.
+    fn factor(&self) -> f64 {
+        match self {
+            ForceUnit::Newton => 1.0,
+            ForceUnit::Kilonewton => 1000.0,
+            ForceUnit::PoundForce => 4.448_221_615_260_5,
+            ForceUnit::KilogramForce => 9.806_65,
+        }
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone, Copy, PartialEq, EnumIter)]
+pub enum LengthUnit {
+    #[default]
+    Millimetre,
+    Inch,
+}
+
+impl fmt::Display for LengthUnit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            LengthUnit::Millimetre => write!(f, "mm"),
+            LengthUnit::Inch => write!(f, "in"),
+        }
+    }
+}
+
+impl LengthUnit {
+    /// mm per unit.
+    fn factor(&self) -> f64 {
+        match self {
+            LengthUnit::Millimetre => 1.0,
+            LengthUnit::Inch => 25.4,
+        }
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone, Copy, PartialEq, EnumIter)]
+pub enum StressUnit {
+    #[default]
+    Megapascal,
+    Psi,
+    Ksi,
+}
+
+impl fmt::Display for StressUnit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            StressUnit::Megapascal => write!(f, "MPa"),
+            StressUnit::Psi => write!(f, "psi"),
+            StressUnit::Ksi => write!(f, "ksi"),
+        }
+    }
+}
+
+impl StressUnit {
+    /// MPa per unit.
+    fn factor(&self) -> f64 {
+        match self {
+            StressUnit::Megapascal => 1.0,
+            StressUnit::Psi => 0.006_894_757_293_168,
+            StressUnit::Ksi => 6.894_757_293_168,
+        }
+    }
+}
+
+/// What a result measures, which decides the unit it is shown in.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum Quantity {
+    Force,
+    Length,
+    Stress,
+    /// In %.
+    Strain,
+    /// In J.
+    Energy,
+    /// Without a unit, like a fit's R².
+    Number,
+}
+
+/// The unit system picked in the user preferences.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct Units {
+    pub force: ForceUnit,
+    pub length: LengthUnit,
+    pub stress: StressUnit,
+}
+
+impl Units {
+    pub fn force(&self) -> Scale {
+        Scale::new(self.force.factor(), self.force.to_string())
+    }
+
+    pub fn length(&self) -> Scale {
+        Scale::new(self.length.factor(), self.length.to_string())
+    }
+
+    pub fn area(&self) -> Scale {
+        Scale::new(self.length.factor().powi(2), format!("{}²", self.length))
+    }
+
+    /// Crosshead speed.
+    pub fn speed(&self) -> Scale {
+        Scale::new(self.length.factor(), format!("{}/s", self.length))
+    }
+
+    pub fn stress(&self) -> Scale {
+        Scale::new(self.stress.factor(), self.stress.to_string())
+    }
+
+    /// Force per length, e.g. the stiffness of the frame.
+    pub fn stiffness(&self) -> Scale {
+        Scale::new(self.force.factor() / self.length.factor(), format!("{}/{}", self.force, self.length))
+    }
+
+    /// Strain is always shown in %.
+    pub fn strain(&self) -> Scale {
+        Scale::new(1.0, "%".to_owned())
+    }
+
+    pub fn limit(&self, limit: LimitKind) -> Scale {
+        match limit {
+            LimitKind::Extension => self.length(),
+            LimitKind::Force => self.force(),
+            LimitKind::Strain => self.strain(),
+        }
+    }
+
+    pub fn quantity(&self, quantity: Quantity) -> Scale {
+        match quantity {
+            Quantity::Force => self.force(),
+            Quantity::Length => self.length(),
+            Quantity::Stress => self.stress(),
+            Quantity::Strain => self.strain(),
+            Quantity::Energy => Scale::new(1.0, "J".to_owned()),
+            Quantity::Number => Scale::new(1.0, String::new()),
+        }
+    }
+
+    pub fn speed_unit(&self, unit: SpeedUnit) -> Scale {
+        match unit {
+            SpeedUnit::Crosshead => self.speed(),
+            SpeedUnit::StrainRate => Scale::new(1.0, "1/s".to_owned()),
+            SpeedUnit::StressRate => Scale::new(self.stress.factor(), format!("{}/s", self.stress)),
+        }
+    }
+}
+
+/// How a quantity stored in its internal unit is shown.
+#[derive(Debug, Clone)]
+pub struct Scale {
+    /// Internal units per shown unit.
+    factor: f64,
+    pub symbol: String,
+}
+
+impl Scale {
+    fn new(factor: f64, symbol: String) -> Self {
+        Self { factor, symbol }
+    }
+
+    /// Time is always shown in s.
+    pub fn seconds() -> Self {
+        Self::new(1.0, "s".to_owned())
+    }
+
+    /// Converts an internal value into the shown unit.
+    pub fn show(&self, value: f64) -> f64 {
+        value / self.factor
+    }
+
+    /// Converts a value in the shown unit into the internal unit.
+    pub fn store(&self, value: f64) -> f64 {
+        value * self.factor
+    }
+
+    /// Formats an internal value in the shown unit, with its symbol.
+    pub fn format(&self, value: f64, decimals: usize) -> String {
+        format!("{:.*}{}", decimals, self.show(value), self.symbol)
+    }
+
+    /// A `DragValue` editing an internal value in the shown unit. Ranges
+    /// and drag speeds set on it are in the shown unit.
+    pub fn drag_value<'a>(&self, value: &'a mut f64) -> egui::DragValue<'a> {
+        let factor = self.factor;
+
+        egui::DragValue::from_get_set(move |shown| {
+            if let Some(shown) = shown {
+                *value = shown * factor;
+            }
+            *value / factor
+        })
+        .suffix(self.symbol.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use strum::IntoEnumIterator;
+
+    use super::*;
+
+    fn every_units() -> impl Iterator<Item = Units> {
+        ForceUnit::iter().flat_map(|force| {
+            LengthUnit::iter().flat_map(move |length| StressUnit::iter().map(move |stress| Units { force, length, stress }))
+        })
+    }
+
+    #[test]
+    fn scales_round_trip() {
+        for units in every_units() {
+            let scales = [units.force(), units.length(), units.area(), units.speed(), units.stress(), units.stiffness(), units.strain()];
+            for scale in scales {
+                for value in [0.0, 1.0, -12.5, 1234.567] {
+                    let round_trip = scale.store(scale.show(value));
+                    assert!((round_trip - value).abs() <= 1e-12 * value.abs().max(1.0), "{} {value} became {round_trip}", scale.symbol);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn scales_convert_to_the_shown_unit() {
+        let units = Units { force: ForceUnit::Kilonewton, length: LengthUnit::Inch, stress: StressUnit::Ksi };
+
+        assert!((units.force().show(2500.0) - 2.5).abs() < 1e-12);
+        assert!((units.length().show(25.4) - 1.0).abs() < 1e-12);
+        assert!((units.area().show(645.16) - 1.0).abs() < 1e-12);
+        assert!((units.stress().show(6.894_757_293_168) - 1.0).abs() < 1e-12);
+        assert!((units.stiffness().store(1.0) - 1000.0 / 25.4).abs() < 1e-9);
+        assert_eq!(units.force().format(2500.0, 1), "2.5kN");
+    }
+
+    #[test]
+    fn metric_units_are_the_internal_units() {
+        let units = Units::default();
+
+        for quantity in [Quantity::Force, Quantity::Length, Quantity::Stress, Quantity::Strain, Quantity::Energy, Quantity::Number] {
+            assert_eq!(units.quantity(quantity).show(42.0), 42.0);
+        }
+    }
+}
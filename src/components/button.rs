@@ -1,5 +1,4 @@
 use eframe::egui::{self, Button, RichText};
-use egui::ImageButton;
 use enum_map::{enum_map, Enum, EnumMap};
 
 use crate::{
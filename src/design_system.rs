@@ -8,6 +8,7 @@ pub const GREY_WHITE: Color32 = Color32::from_rgb(231, 231, 231);
 pub const BACKGROUND: Color32 = Color32::from_rgb(11, 16, 26);
 pub const YELLOW: Color32 = Color32::from_rgb(254, 216, 67);
 pub const PRIMARY_COLOR: Color32 = Color32::from_rgb(204, 0, 2);
+pub const GREEN: Color32 = Color32::from_rgb(0, 158, 115);
 
 pub const PRIMARY_BUTTON_BACKGROUND_COLOR: Color32 = PRIMARY_COLOR;
 pub const PRIMARY_BUTTON_TEXT_COLOR: Color32 = WHITE;
@@ -6,4 +6,7 @@ mod design_system;
 
 pub use app::TensileTestingApp;
 pub use components::*;
-pub use design_system::*;
\ No newline at end of file
+pub use design_system::*;
+
+/// Name the app runs under, its state and test history are stored in a folder of this name.
+pub const APP_ID: &str = "Industrio Tensile Tester";
\ No newline at end of file